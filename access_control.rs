@@ -4,11 +4,17 @@ use crate::errors::AccessControlError;
 use crate::traits::RoleType;
 pub const DEFAULT_ADMIN_ROLE: RoleType = 0;
 
+pub type RoleMember = (RoleType, Option<AccountId>);
+pub type RoleMemberIndex = (RoleType, u32);
+
 #[derive(Default, Debug)]
 #[ink::storage_item]
 pub struct AccessControlData {
     pub admin_roles: Mapping<RoleType, RoleType>,
-    pub members: Mapping<(RoleType, Option<AccountId>), ()>,
+    pub members: Mapping<RoleMember, ()>,
+    pub role_members: Mapping<RoleMemberIndex, Option<AccountId>>,
+    pub role_member_indexes: Mapping<RoleMember, u32>,
+    pub role_member_counts: Mapping<RoleType, u32>,
 }
 
 impl AccessControlData {
@@ -17,11 +23,60 @@ impl AccessControlData {
     }
 
     pub fn _add(&mut self, role: RoleType, member: &Option<AccountId>) {
+        if self._has_role(role, member) {
+            return;
+        }
         self.members.insert((role, member), &());
+        self._push_member(role, member);
+    }
+
+    /// Indexes `member` of `role` if it was granted before members were enumerable.
+    pub fn _index(
+        &mut self,
+        role: RoleType,
+        member: &Option<AccountId>,
+    ) -> Result<(), AccessControlError> {
+        if !self._has_role(role, member) {
+            return Err(AccessControlError::MissingRole);
+        }
+        if !self.role_member_indexes.contains((role, member)) {
+            self._push_member(role, member);
+        }
+        Ok(())
+    }
+
+    fn _push_member(&mut self, role: RoleType, member: &Option<AccountId>) {
+        let count = self._get_role_member_count(role);
+        self.role_members.insert((role, count), member);
+        self.role_member_indexes.insert((role, member), &count);
+        self.role_member_counts.insert(role, &(count + 1));
     }
 
     pub fn _remove(&mut self, role: RoleType, member: &Option<AccountId>) {
         self.members.remove((role, member));
+        let Some(index) = self.role_member_indexes.take((role, member)) else {
+            return;
+        };
+        // Move the last member into the freed slot to keep indexes contiguous
+        let last_index = self._get_role_member_count(role) - 1;
+        if index != last_index {
+            let last_member = self
+                .role_members
+                .get((role, last_index))
+                .unwrap_or_default();
+            self.role_members.insert((role, index), &last_member);
+            self.role_member_indexes.insert((role, last_member), &index);
+        }
+        self.role_members.remove((role, last_index));
+        self.role_member_counts.insert(role, &last_index);
+    }
+
+    pub fn _get_role_member(&self, role: RoleType, index: u32) -> Option<AccountId> {
+        self.role_members.get((role, index)).flatten()
+    }
+
+    pub fn _get_role_member_count(&self, role: RoleType) -> u32 {
+        self.role_member_counts.get(role).unwrap_or_default()
     }
 
     pub fn _get_role_admin(&self, role: RoleType) -> Option<RoleType> {
@@ -1,10 +1,13 @@
-use ink::prelude::string::String;
+use ink::{prelude::string::String, storage::Lazy};
 use crate::errors::PSP22Error;
 
+/// Cap value standing for an unlimited supply.
+pub const UNLIMITED_CAP: u128 = u128::MAX;
+
 #[ink::storage_item]
 #[derive(Debug, Default)]
 pub struct Capped {
-    cap: u128,
+    cap: Lazy<u128>,
 }
 
 impl Capped {
@@ -12,11 +15,38 @@ impl Capped {
         if cap == 0 {
             return Err(PSP22Error::Custom(String::from("Cap must be above 0")));
         }
-        self.cap = cap;
+        self.cap.set(&cap);
         Ok(())
     }
 
     pub fn cap(&self) -> u128 {
-        self.cap
+        self.cap.get().unwrap_or_default()
+    }
+
+    /// Returns the cap, `None` when the supply is unlimited.
+    pub fn _get_cap(&self) -> Option<u128> {
+        let cap = self.cap();
+        (cap != UNLIMITED_CAP).then_some(cap)
+    }
+
+    /// Sets a cap of at least `total_supply`, `None` removing the cap.
+    pub fn _set_cap(
+        &mut self,
+        new_cap: Option<u128>,
+        total_supply: u128,
+    ) -> Result<(), PSP22Error> {
+        let cap = new_cap.unwrap_or(UNLIMITED_CAP);
+        if cap == 0 || cap < total_supply {
+            return Err(PSP22Error::InvalidCap);
+        }
+        self.cap.set(&cap);
+        Ok(())
+    }
+
+    pub fn _check_cap(&self, total_supply: u128, value: u128) -> Result<(), PSP22Error> {
+        if total_supply.saturating_add(value) > self.cap() {
+            return Err(PSP22Error::CapExceeded);
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,60 @@
+use crate::errors::PSP22Error;
+use ink::{
+    primitives::AccountId,
+    storage::{Lazy, Mapping},
+};
+
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct ComplianceData {
+    allowlist_mode: Lazy<bool>,
+    blocked: Mapping<AccountId, ()>,
+    allowed: Mapping<AccountId, ()>,
+}
+
+impl ComplianceData {
+    /// Whether only allowed accounts may hold and move tokens, blocked accounts are
+    /// restricted in both modes.
+    pub fn allowlist_mode(&self) -> bool {
+        self.allowlist_mode.get().unwrap_or_default()
+    }
+
+    pub fn is_blocked(&self, account: AccountId) -> bool {
+        self.blocked.contains(account)
+    }
+
+    pub fn is_allowed(&self, account: AccountId) -> bool {
+        self.allowed.contains(account)
+    }
+
+    pub fn _set_allowlist_mode(&mut self, enabled: bool) {
+        self.allowlist_mode.set(&enabled);
+    }
+
+    /// Returns `false` if `account` was already blocked.
+    pub fn _block(&mut self, account: AccountId) -> bool {
+        self.blocked.insert(account, &()).is_none()
+    }
+
+    /// Returns `false` if `account` was not blocked.
+    pub fn _unblock(&mut self, account: AccountId) -> bool {
+        self.blocked.take(account).is_some()
+    }
+
+    /// Returns `false` if `account` was already allowed.
+    pub fn _allow(&mut self, account: AccountId) -> bool {
+        self.allowed.insert(account, &()).is_none()
+    }
+
+    /// Returns `false` if `account` was not allowed.
+    pub fn _disallow(&mut self, account: AccountId) -> bool {
+        self.allowed.take(account).is_some()
+    }
+
+    pub fn _check_account(&self, account: AccountId) -> Result<(), PSP22Error> {
+        if self.is_blocked(account) || (self.allowlist_mode() && !self.is_allowed(account)) {
+            return Err(PSP22Error::AccountRestricted);
+        }
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@ use ink::prelude::string::String;
 use ink::{
     prelude::{vec, vec::Vec},
     primitives::AccountId,
-    storage::Mapping,
+    storage::{Lazy, Mapping},
 };
 
 pub enum PSP22Event {
@@ -19,29 +19,145 @@ pub enum PSP22Event {
     },
 }
 
+/// Extension points run around every balance change, `None` standing for mint or burn.
+/// Returning an error from either hook vetoes the change.
+pub trait PSP22Hooks {
+    fn _before_token_transfer(
+        &mut self,
+        _from: Option<AccountId>,
+        _to: Option<AccountId>,
+        _value: u128,
+    ) -> Result<(), PSP22Error> {
+        Ok(())
+    }
+
+    fn _after_token_transfer(
+        &mut self,
+        _from: Option<AccountId>,
+        _to: Option<AccountId>,
+        _value: u128,
+    ) -> Result<(), PSP22Error> {
+        Ok(())
+    }
+}
+
+/// No-op hooks.
+impl PSP22Hooks for () {}
+
+/// Upper bound of the transfer fee, in basis points.
+pub const MAX_TRANSFER_FEE: u16 = 1_000;
+
+/// Maximum number of pending locks on an account.
+pub const MAX_LOCKS: usize = 32;
+
+/// `(unlock timestamp, amount, sender)` of tokens that cannot move before that timestamp.
+pub type Lock = (u64, u128, AccountId);
+
+/// Dividend accumulated per share as `(whole units, fraction in units of 2^-128)`, a 256-bit
+/// fixed point number precise enough for any supply that fits in a `u128`.
+pub type DividendPerShare = (u128, u128);
+
+/// Snapshots of an account balance, or of the total supply when the account is `None`.
+pub type SnapshotAccount = Option<AccountId>;
+/// `(snapshot id, value)` recorded the first time a value changes after that snapshot.
+pub type SnapshotEntry = (u32, u128);
+
 #[ink::storage_item]
 #[derive(Debug, Default)]
 pub struct PSP22Data {
-    total_supply: u128,
+    total_supply: Lazy<u128>,
+    /// Shares of each account, equal to its balance unless the supply is elastic.
     balances: Mapping<AccountId, u128>,
     allowances: Mapping<(AccountId, AccountId), u128>,
+    current_snapshot_id: Lazy<u32>,
+    snapshots: Mapping<(SnapshotAccount, u32), SnapshotEntry>,
+    snapshot_counts: Mapping<SnapshotAccount, u32>,
+    transfer_fee: Lazy<u16>,
+    transfer_fee_recipient: Lazy<Option<AccountId>>,
+    fee_exempt: Mapping<AccountId, ()>,
+    locks: Mapping<AccountId, Vec<Lock>>,
+    reward_token: Lazy<Option<AccountId>>,
+    dividend_per_share: Lazy<DividendPerShare>,
+    /// Part of the distributions too small to credit to every share, in units of 2^-128.
+    dividend_remainder: Lazy<u128>,
+    /// `dividend_per_share` when the dividend of each account was last credited.
+    dividend_checkpoints: Mapping<AccountId, DividendPerShare>,
+    credited_dividends: Mapping<AccountId, u128>,
+    dividend_reserve: Lazy<u128>,
+    elastic: Lazy<bool>,
+    total_shares: Lazy<u128>,
 }
 
 impl PSP22Data {
     pub fn total_supply(&self) -> u128 {
-        self.total_supply
+        self.total_supply.get().unwrap_or_default()
     }
 
     pub fn balance_of(&self, owner: AccountId) -> u128 {
+        self._to_units(self.shares_of(owner))
+    }
+
+    pub fn shares_of(&self, owner: AccountId) -> u128 {
         self.balances.get(owner).unwrap_or_default()
     }
 
+    pub fn total_shares(&self) -> u128 {
+        if self.elastic() {
+            self.total_shares.get().unwrap_or_default()
+        } else {
+            self.total_supply()
+        }
+    }
+
+    /// Whether balances are shares of a total supply that can be rebased.
+    pub fn elastic(&self) -> bool {
+        self.elastic.get().unwrap_or_default()
+    }
+
+    /// Restores the supply and snapshot counter of an older storage layout.
+    pub fn _restore(&mut self, total_supply: u128, current_snapshot_id: u32) {
+        self.total_supply.set(&total_supply);
+        self.current_snapshot_id.set(&current_snapshot_id);
+    }
+
+    /// Counts each unit of a supply minted before shares were tracked as one share.
+    pub fn _restore_total_shares(&mut self) {
+        self.total_shares.set(&self.total_supply());
+    }
+
+    /// Must be called before the first mint.
+    pub fn _init_elastic(&mut self) {
+        self.elastic.set(&true);
+    }
+
+    /// Sets the total supply, scaling every balance by the same factor.
+    pub fn _rebase(&mut self, new_total_supply: u128) -> Result<(), PSP22Error> {
+        if !self.elastic() {
+            return Err(PSP22Error::NotElastic);
+        }
+        if self.total_shares() == 0 || new_total_supply == 0 {
+            return Err(PSP22Error::InvalidRebase);
+        }
+        self._update_total_supply_snapshot();
+        self.total_supply.set(&new_total_supply);
+        Ok(())
+    }
+
+    /// Extensions relying on fixed amounts break once balances are shares.
+    pub fn _check_not_elastic(&self) -> Result<(), PSP22Error> {
+        if self.elastic() {
+            return Err(PSP22Error::ElasticUnsupported);
+        }
+        Ok(())
+    }
+
     pub fn allowance(&self, owner: AccountId, spender: AccountId) -> u128 {
         self.allowances.get((owner, spender)).unwrap_or_default()
     }
 
     pub fn transfer(
         &mut self,
+        hooks: &mut impl PSP22Hooks,
         caller: AccountId,
         to: AccountId,
         value: u128,
@@ -49,30 +165,13 @@ impl PSP22Data {
         if caller == to || value == 0 {
             return Ok(vec![]);
         }
-        let from_balance = self.balance_of(caller);
-        if from_balance < value {
-            return Err(PSP22Error::InsufficientBalance);
-        }
-
-        if from_balance == value {
-            self.balances.remove(caller);
-        } else {
-            self.balances
-                .insert(caller, &(from_balance.saturating_sub(value)));
-        }
-        let to_balance = self.balance_of(to);
-        // Total supply is limited by u128.MAX so no overflow is possible
-        self.balances
-            .insert(to, &(to_balance.saturating_add(value)));
-        Ok(vec![PSP22Event::Transfer {
-            from: Some(caller),
-            to: Some(to),
-            value,
-        }])
+        self._check_spendable(caller, value)?;
+        self._transfer_with_fee(hooks, caller, to, value)
     }
 
     pub fn transfer_from(
         &mut self,
+        hooks: &mut impl PSP22Hooks,
         caller: AccountId,
         from: AccountId,
         to: AccountId,
@@ -82,47 +181,47 @@ impl PSP22Data {
             return Ok(vec![]);
         }
         if caller == from {
-            return self.transfer(caller, to, value);
+            return self.transfer(hooks, caller, to, value);
         }
 
         let allowance = self.allowance(from, caller);
         if allowance < value {
             return Err(PSP22Error::InsufficientAllowance);
         }
-        let from_balance = self.balance_of(from);
-        if from_balance < value {
-            return Err(PSP22Error::InsufficientBalance);
-        }
-
+        self._check_spendable(from, value)?;
         if allowance == value {
             self.allowances.remove((from, caller));
         } else {
             self.allowances
                 .insert((from, caller), &(allowance.saturating_sub(value)));
         }
+        let mut events = vec![PSP22Event::Approval {
+            owner: from,
+            spender: caller,
+            amount: allowance.saturating_sub(value),
+        }];
+        events.extend(self._transfer_with_fee(hooks, from, to, value)?);
+        Ok(events)
+    }
 
-        if from_balance == value {
-            self.balances.remove(from);
-        } else {
-            self.balances
-                .insert(from, &(from_balance.saturating_sub(value)));
-        }
-        let to_balance = self.balance_of(to);
-        // Total supply is limited by u128.MAX so no overflow is possible
-        self.balances
-            .insert(to, &(to_balance.saturating_add(value)));
-        Ok(vec![
-            PSP22Event::Approval {
-                owner: from,
-                spender: caller,
-                amount: allowance.saturating_sub(value),
-            },
-            PSP22Event::Transfer {
-                from: Some(from),
-                to: Some(to),
-                value,
-            },
-        ])
+    /// Transfers `value` to `to`, keeping the amount received locked until `unlock_at`.
+    pub fn transfer_locked(
+        &mut self,
+        hooks: &mut impl PSP22Hooks,
+        caller: AccountId,
+        to: AccountId,
+        value: u128,
+        unlock_at: u64,
+    ) -> Result<Vec<PSP22Event>, PSP22Error> {
+        self._check_not_elastic()?;
+        if caller == to || value == 0 {
+            return Ok(vec![]);
+        }
+        let received = value - self.transfer_fee_for(caller, to, value);
+        self._add_lock(to, caller, received, unlock_at)?;
+        let events = self.transfer(hooks, caller, to, value)?;
+        self._lock(to, caller, received, unlock_at)?;
+        Ok(events)
     }
 
     pub fn approve(
@@ -191,48 +290,1074 @@ impl PSP22Data {
         }])
     }
 
-    pub fn _mint_to(&mut self, to: AccountId, value: u128) -> Result<Vec<PSP22Event>, PSP22Error> {
+    pub fn _mint_to(
+        &mut self,
+        hooks: &mut impl PSP22Hooks,
+        to: AccountId,
+        value: u128,
+    ) -> Result<Vec<PSP22Event>, PSP22Error> {
         if value == 0 {
             return Ok(vec![]);
         }
-        let new_supply = self
-            .total_supply
-            .checked_add(value)
-            .ok_or(PSP22Error::Custom(String::from(
-                "Max PSP22 supply exceeded. Max supply limited to 2^128-1.",
-            )))?;
-        self.total_supply = new_supply;
-        let new_balance = self.balance_of(to).saturating_add(value);
-        self.balances.insert(to, &new_balance);
-        Ok(vec![PSP22Event::Transfer {
-            from: None,
-            to: Some(to),
-            value,
-        }])
+        self._update(hooks, None, Some(to), value)
     }
 
     pub fn _burn_from(
         &mut self,
+        hooks: &mut impl PSP22Hooks,
         from: AccountId,
         value: u128,
     ) -> Result<Vec<PSP22Event>, PSP22Error> {
         if value == 0 {
             return Ok(vec![]);
         }
-        let balance = self.balance_of(from);
-        if balance < value {
+        self._update(hooks, Some(from), None, value)
+    }
+
+    /// Moves `value` from `from` to `to`, minting when `from` is `None` and burning when `to`
+    /// is `None`. Every balance change goes through here, between the two hooks.
+    pub fn _update(
+        &mut self,
+        hooks: &mut impl PSP22Hooks,
+        from: Option<AccountId>,
+        to: Option<AccountId>,
+        value: u128,
+    ) -> Result<Vec<PSP22Event>, PSP22Error> {
+        hooks._before_token_transfer(from, to, value)?;
+        let shares = match from {
+            Some(from) => {
+                self._check_spendable(from, value)?;
+                let from_shares = self.shares_of(from);
+                // Moving a whole balance moves all its shares, leaving no rounding dust.
+                // Partial debits round up so the sender never keeps more than it should,
+                // and must leave it at least one share.
+                let shares = if value == self.balance_of(from) {
+                    from_shares
+                } else {
+                    let shares = self._to_shares_up(value);
+                    if shares >= from_shares {
+                        return Err(PSP22Error::RemainderTooSmall);
+                    }
+                    shares
+                };
+                self._update_account_snapshot(from);
+                self._credit_dividend(from);
+                if from_shares == shares {
+                    self.balances.remove(from);
+                } else {
+                    self.balances
+                        .insert(from, &(from_shares.saturating_sub(shares)));
+                }
+                shares
+            }
+            None => {
+                let shares = self._to_shares(value);
+                if shares == 0 && value > 0 {
+                    return Err(PSP22Error::ZeroShares);
+                }
+                let supply_exceeded = || {
+                    PSP22Error::Custom(String::from(
+                        "Max PSP22 supply exceeded. Max supply limited to 2^128-1.",
+                    ))
+                };
+                let new_supply = self
+                    .total_supply()
+                    .checked_add(value)
+                    .ok_or_else(supply_exceeded)?;
+                let new_shares = self
+                    .total_shares
+                    .get()
+                    .unwrap_or_default()
+                    .checked_add(shares)
+                    .ok_or_else(supply_exceeded)?;
+                self._update_total_supply_snapshot();
+                self.total_supply.set(&new_supply);
+                self.total_shares.set(&new_shares);
+                shares
+            }
+        };
+        match to {
+            Some(to) => {
+                self._update_account_snapshot(to);
+                self._credit_dividend(to);
+                // Total shares are limited by u128.MAX so no overflow is possible
+                let to_shares = self.shares_of(to).saturating_add(shares);
+                self.balances.insert(to, &to_shares);
+            }
+            None => {
+                self._update_total_supply_snapshot();
+                self.total_supply
+                    .set(&self.total_supply().saturating_sub(value));
+                let total_shares = self.total_shares.get().unwrap_or_default();
+                self.total_shares.set(&total_shares.saturating_sub(shares));
+            }
+        }
+        hooks._after_token_transfer(from, to, value)?;
+        Ok(vec![PSP22Event::Transfer { from, to, value }])
+    }
+
+    /// Burns `value` tokens of `from`, spending the allowance of `caller` if it is not `from`.
+    pub fn burn_from(
+        &mut self,
+        hooks: &mut impl PSP22Hooks,
+        caller: AccountId,
+        from: AccountId,
+        value: u128,
+    ) -> Result<Vec<PSP22Event>, PSP22Error> {
+        if caller == from || value == 0 {
+            return self._burn_from(hooks, from, value);
+        }
+        let allowance = self.allowance(from, caller);
+        if allowance < value {
+            return Err(PSP22Error::InsufficientAllowance);
+        }
+        let mut events = self._burn_from(hooks, from, value)?;
+        events.extend(self.decrease_allowance(from, caller, value)?);
+        Ok(events)
+    }
+
+    pub fn current_snapshot_id(&self) -> u32 {
+        self.current_snapshot_id.get().unwrap_or_default()
+    }
+
+    pub fn balance_of_at(&self, account: AccountId, snapshot_id: u32) -> Result<u128, PSP22Error> {
+        Ok(self
+            ._value_at(Some(account), snapshot_id)?
+            .unwrap_or_else(|| self.balance_of(account)))
+    }
+
+    pub fn total_supply_at(&self, snapshot_id: u32) -> Result<u128, PSP22Error> {
+        Ok(self
+            ._value_at(None, snapshot_id)?
+            .unwrap_or(self.total_supply()))
+    }
+
+    /// Starts a new snapshot and returns its id.
+    pub fn _snapshot(&mut self) -> u32 {
+        self.current_snapshot_id
+            .set(&self.current_snapshot_id().saturating_add(1));
+        self.current_snapshot_id()
+    }
+
+    /// Returns the value recorded for `snapshot_id`, or `None` if it has not changed since.
+    fn _value_at(
+        &self,
+        account: SnapshotAccount,
+        snapshot_id: u32,
+    ) -> Result<Option<u128>, PSP22Error> {
+        if snapshot_id == 0 || snapshot_id > self.current_snapshot_id() {
+            return Err(PSP22Error::SnapshotNotFound);
+        }
+        // Find the first entry recorded at or after `snapshot_id`
+        let (mut low, mut high) = (0, self.snapshot_counts.get(account).unwrap_or_default());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (id, _) = self.snapshots.get((account, mid)).unwrap_or_default();
+            if id < snapshot_id {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(self.snapshots.get((account, low)).map(|(_, value)| value))
+    }
+
+    /// Balance of `account` that cannot move yet.
+    pub fn locked_balance_of(&self, account: AccountId) -> u128 {
+        let now = Self::_now();
+        self.locks
+            .get(account)
+            .unwrap_or_default()
+            .iter()
+            .filter(|lock| lock.0 > now)
+            .fold(0, |locked, lock| locked.saturating_add(lock.1))
+    }
+
+    pub fn unlocked_balance_of(&self, account: AccountId) -> u128 {
+        self.balance_of(account)
+            .saturating_sub(self.locked_balance_of(account))
+    }
+
+    /// Locks `amount` of the balance of `account` sent by `sender` until `unlock_at`,
+    /// dropping expired locks.
+    pub fn _lock(
+        &mut self,
+        account: AccountId,
+        sender: AccountId,
+        amount: u128,
+        unlock_at: u64,
+    ) -> Result<(), PSP22Error> {
+        let locks = self._add_lock(account, sender, amount, unlock_at)?;
+        self._set_locks(account, locks);
+        Ok(())
+    }
+
+    /// Removes the lock `sender` put on `account` until `unlock_at` and returns its amount,
+    /// so the recipient can send back locked tokens it does not want.
+    pub fn _reject_lock(
+        &mut self,
+        account: AccountId,
+        sender: AccountId,
+        unlock_at: u64,
+    ) -> Result<u128, PSP22Error> {
+        let mut locks = self.locks_of(account);
+        let index = locks
+            .iter()
+            .position(|lock| lock.0 == unlock_at && lock.2 == sender)
+            .ok_or(PSP22Error::LockNotFound)?;
+        let (_, amount, _) = locks.swap_remove(index);
+        self._set_locks(account, locks);
+        Ok(amount)
+    }
+
+    /// Pending locks of `account` with the new lock added, merged into the lock of the same
+    /// sender and unlock time if there is one.
+    fn _add_lock(
+        &self,
+        account: AccountId,
+        sender: AccountId,
+        amount: u128,
+        unlock_at: u64,
+    ) -> Result<Vec<Lock>, PSP22Error> {
+        let mut locks = self.locks_of(account);
+        if amount == 0 || unlock_at <= Self::_now() {
+            return Ok(locks);
+        }
+        match locks
+            .iter()
+            .position(|lock| lock.0 == unlock_at && lock.2 == sender)
+        {
+            Some(index) => locks[index].1 = locks[index].1.saturating_add(amount),
+            None if locks.len() >= MAX_LOCKS => return Err(PSP22Error::TooManyLocks),
+            None => locks.push((unlock_at, amount, sender)),
+        }
+        Ok(locks)
+    }
+
+    /// Unexpired locks of `account`.
+    pub fn locks_of(&self, account: AccountId) -> Vec<Lock> {
+        let now = Self::_now();
+        let mut locks = self.locks.get(account).unwrap_or_default();
+        locks.retain(|lock| lock.0 > now);
+        locks
+    }
+
+    fn _set_locks(&mut self, account: AccountId, locks: Vec<Lock>) {
+        if locks.is_empty() {
+            self.locks.remove(account);
+        } else {
+            self.locks.insert(account, &locks);
+        }
+    }
+
+    /// Releases just enough locked tokens of `account` to spend `value`, latest unlocks
+    /// first. Used before seizing its tokens.
+    pub fn _unlock_for(&mut self, account: AccountId, value: u128) {
+        let mut excess = value.saturating_sub(self.unlocked_balance_of(account));
+        if excess == 0 {
+            return;
+        }
+        let mut locks = self.locks_of(account);
+        locks.sort_by_key(|lock| core::cmp::Reverse(lock.0));
+        for lock in locks.iter_mut() {
+            let released = lock.1.min(excess);
+            lock.1 -= released;
+            excess -= released;
+        }
+        locks.retain(|lock| lock.1 > 0);
+        self._set_locks(account, locks);
+    }
+
+    fn _check_spendable(&self, account: AccountId, value: u128) -> Result<(), PSP22Error> {
+        if self.balance_of(account) < value {
             return Err(PSP22Error::InsufficientBalance);
         }
-        if balance == value {
-            self.balances.remove(from);
+        if self.unlocked_balance_of(account) < value {
+            return Err(PSP22Error::TokensLocked);
+        }
+        Ok(())
+    }
+
+    fn _now() -> u64 {
+        ink::env::block_timestamp::<ink::env::DefaultEnvironment>()
+    }
+
+    fn _this() -> AccountId {
+        ink::env::account_id::<ink::env::DefaultEnvironment>()
+    }
+
+    /// Token paid out as dividends, the native currency when `None`.
+    pub fn reward_token(&self) -> Option<AccountId> {
+        self.reward_token.get().flatten()
+    }
+
+    /// Dividends distributed and not claimed yet.
+    pub fn dividend_reserve(&self) -> u128 {
+        self.dividend_reserve.get().unwrap_or_default()
+    }
+
+    pub fn withdrawable_dividend_of(&self, account: AccountId) -> u128 {
+        self.credited_dividends
+            .get(account)
+            .unwrap_or_default()
+            .saturating_add(self._pending_dividend(account))
+    }
+
+    /// Can only change before the first distribution.
+    pub fn _set_reward_token(&mut self, reward_token: Option<AccountId>) -> Result<(), PSP22Error> {
+        if self.dividend_per_share() != (0, 0) {
+            return Err(PSP22Error::InvalidRewardAsset);
+        }
+        self.reward_token.set(&reward_token);
+        Ok(())
+    }
+
+    /// Credits `value` of the reward asset to holders pro rata to their balances. Tokens
+    /// held by the contract itself, such as vesting escrow, earn nothing since it cannot
+    /// claim. The part too small to credit to every share is carried over to the next
+    /// distribution.
+    pub fn _distribute(&mut self, value: u128) -> Result<(), PSP22Error> {
+        let earning_shares = self
+            .total_shares()
+            .saturating_sub(self.shares_of(Self::_this()));
+        if earning_shares == 0 {
+            return Err(PSP22Error::NoSupply);
+        }
+        let (whole, fraction) = self.dividend_per_share();
+        let (added_fraction, remainder) = div_wide(
+            value % earning_shares,
+            self.dividend_remainder.get().unwrap_or_default(),
+            earning_shares,
+        );
+        let (fraction, carry) = fraction.overflowing_add(added_fraction);
+        let whole = whole
+            .saturating_add(value / earning_shares)
+            .saturating_add(carry as u128);
+        self.dividend_per_share.set(&(whole, fraction));
+        self.dividend_remainder.set(&remainder);
+        self.dividend_reserve
+            .set(&self.dividend_reserve().saturating_add(value));
+        Ok(())
+    }
+
+    /// Marks the withdrawable dividend of `account` as claimed and returns it.
+    pub fn _claim(&mut self, account: AccountId) -> u128 {
+        self._credit_dividend(account);
+        let value = self.credited_dividends.take(account).unwrap_or_default();
+        if value > 0 {
+            self.dividend_reserve
+                .set(&self.dividend_reserve().saturating_sub(value));
+        }
+        value
+    }
+
+    fn dividend_per_share(&self) -> DividendPerShare {
+        self.dividend_per_share.get().unwrap_or_default()
+    }
+
+    /// Dividend earned by the current shares of `account` since it was last credited.
+    fn _pending_dividend(&self, account: AccountId) -> u128 {
+        if account == Self::_this() {
+            return 0;
+        }
+        let (whole, fraction) = self.dividend_per_share();
+        let (last_whole, last_fraction) =
+            self.dividend_checkpoints.get(account).unwrap_or_default();
+        let (fraction, borrow) = fraction.overflowing_sub(last_fraction);
+        let whole = whole
+            .saturating_sub(last_whole)
+            .saturating_sub(borrow as u128);
+        let shares = self.shares_of(account);
+        shares
+            .saturating_mul(whole)
+            .saturating_add(wide_mul(shares, fraction).0)
+    }
+
+    /// Credits the pending dividend of `account`, before its shares change.
+    fn _credit_dividend(&mut self, account: AccountId) {
+        let dividend_per_share = self.dividend_per_share();
+        if dividend_per_share == (0, 0) || account == Self::_this() {
+            return;
+        }
+        let pending = self._pending_dividend(account);
+        if pending > 0 {
+            let credited = self.credited_dividends.get(account).unwrap_or_default();
+            self.credited_dividends
+                .insert(account, &credited.saturating_add(pending));
+        }
+        self.dividend_checkpoints
+            .insert(account, &dividend_per_share);
+    }
+
+    /// Transfer fee in basis points.
+    pub fn transfer_fee(&self) -> u16 {
+        self.transfer_fee.get().unwrap_or_default()
+    }
+
+    /// Receiver of transfer fees, fees are burned when `None`.
+    pub fn transfer_fee_recipient(&self) -> Option<AccountId> {
+        self.transfer_fee_recipient.get().flatten()
+    }
+
+    pub fn is_fee_exempt(&self, account: AccountId) -> bool {
+        self.fee_exempt.contains(account)
+    }
+
+    /// Fee taken on a transfer of `value` between `from` and `to`.
+    pub fn transfer_fee_for(&self, from: AccountId, to: AccountId, value: u128) -> u128 {
+        if self.transfer_fee() == 0 || self.is_fee_exempt(from) || self.is_fee_exempt(to) {
+            return 0;
+        }
+        mul_div(value, self.transfer_fee() as u128, 10_000)
+    }
+
+    pub fn _set_transfer_fee(
+        &mut self,
+        fee: u16,
+        fee_recipient: Option<AccountId>,
+    ) -> Result<(), PSP22Error> {
+        if fee > MAX_TRANSFER_FEE {
+            return Err(PSP22Error::InvalidTransferFee);
+        }
+        self.transfer_fee.set(&fee);
+        self.transfer_fee_recipient.set(&fee_recipient);
+        Ok(())
+    }
+
+    pub fn _set_fee_exempt(&mut self, account: AccountId, exempt: bool) {
+        if exempt {
+            self.fee_exempt.insert(account, &());
         } else {
-            self.balances.insert(from, &(balance.saturating_sub(value)));
+            self.fee_exempt.remove(account);
         }
-        self.total_supply = self.total_supply.saturating_sub(value);
-        Ok(vec![PSP22Event::Transfer {
-            from: Some(from),
-            to: None,
-            value,
-        }])
+    }
+
+    /// Moves `value` from `from` to `to`, sending the transfer fee out of it to the fee
+    /// recipient or burning it.
+    fn _transfer_with_fee(
+        &mut self,
+        hooks: &mut impl PSP22Hooks,
+        from: AccountId,
+        to: AccountId,
+        value: u128,
+    ) -> Result<Vec<PSP22Event>, PSP22Error> {
+        let fee = self.transfer_fee_for(from, to, value);
+        let mut events = self._update(hooks, Some(from), Some(to), value - fee)?;
+        if fee > 0 {
+            let recipient = self.transfer_fee_recipient();
+            events.extend(self._update(hooks, Some(from), recipient, fee)?);
+        }
+        Ok(events)
+    }
+
+    fn _to_units(&self, shares: u128) -> u128 {
+        let total_shares = self.total_shares();
+        if !self.elastic() || total_shares == 0 {
+            return shares;
+        }
+        mul_div(shares, self.total_supply(), total_shares)
+    }
+
+    fn _to_shares(&self, units: u128) -> u128 {
+        if !self.elastic() || self.total_supply() == 0 || self.total_shares() == 0 {
+            return units;
+        }
+        mul_div(units, self.total_shares(), self.total_supply())
+    }
+
+    fn _to_shares_up(&self, units: u128) -> u128 {
+        if !self.elastic() || self.total_supply() == 0 || self.total_shares() == 0 {
+            return units;
+        }
+        mul_div_up(units, self.total_shares(), self.total_supply())
+    }
+
+    fn _update_account_snapshot(&mut self, account: AccountId) {
+        self._update_snapshot(Some(account), self.balance_of(account));
+    }
+
+    fn _update_total_supply_snapshot(&mut self) {
+        self._update_snapshot(None, self.total_supply());
+    }
+
+    /// Records `current_value` as the value at the current snapshot, before it changes.
+    fn _update_snapshot(&mut self, account: SnapshotAccount, current_value: u128) {
+        let current_id = self.current_snapshot_id();
+        if current_id == 0 {
+            return;
+        }
+        let count = self.snapshot_counts.get(account).unwrap_or_default();
+        let last_id = match count {
+            0 => 0,
+            _ => {
+                self.snapshots
+                    .get((account, count - 1))
+                    .unwrap_or_default()
+                    .0
+            }
+        };
+        if last_id < current_id {
+            self.snapshots
+                .insert((account, count), &(current_id, current_value));
+            self.snapshot_counts.insert(account, &(count + 1));
+        }
+    }
+}
+
+/// `a * b / c` rounded down with a 256-bit intermediate product, saturating at `u128::MAX`.
+pub(crate) fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    let (hi, lo) = wide_mul(a, b);
+    if hi == 0 {
+        return lo / c;
+    }
+    if hi >= c {
+        return u128::MAX;
+    }
+    div_wide(hi, lo, c).0
+}
+
+/// `a * b / c` rounded up with a 256-bit intermediate product, saturating at `u128::MAX`.
+pub(crate) fn mul_div_up(a: u128, b: u128, c: u128) -> u128 {
+    let (hi, lo) = wide_mul(a, b);
+    if hi >= c {
+        return u128::MAX;
+    }
+    let (quotient, remainder) = if hi == 0 {
+        (lo / c, lo % c)
+    } else {
+        div_wide(hi, lo, c)
+    };
+    quotient.saturating_add((remainder > 0) as u128)
+}
+
+/// `a * b` as its high and low 128 bits.
+fn wide_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo, b_hi, b_lo) = (a >> 64, a & MASK, b >> 64, b & MASK);
+    let (ll, lh, hl, hh) = (a_lo * b_lo, a_lo * b_hi, a_hi * b_lo, a_hi * b_hi);
+    let mid = (ll >> 64) + (lh & MASK) + (hl & MASK);
+    let lo = (ll & MASK) | (mid << 64);
+    let hi = hh + (lh >> 64) + (hl >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+/// Quotient and remainder of `(hi * 2^128 + lo) / c`, the quotient fits since `hi < c`.
+fn div_wide(hi: u128, lo: u128, c: u128) -> (u128, u128) {
+    let (mut remainder, mut quotient) = (hi, 0u128);
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((lo >> bit) & 1);
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1 << bit;
+        }
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ink::env::{
+        test::{default_accounts, DefaultAccounts},
+        DefaultEnvironment,
+    };
+
+    fn accounts() -> DefaultAccounts<DefaultEnvironment> {
+        default_accounts::<DefaultEnvironment>()
+    }
+
+    fn holders() -> Vec<AccountId> {
+        let accounts = accounts();
+        vec![
+            accounts.alice,
+            accounts.bob,
+            accounts.charlie,
+            accounts.django,
+            accounts.eve,
+            accounts.frank,
+        ]
+    }
+
+    /// Records every balance change and vetoes the ones involving `blocked`.
+    #[derive(Default)]
+    struct RecordingHooks {
+        blocked: Option<AccountId>,
+        before: Vec<(Option<AccountId>, Option<AccountId>, u128)>,
+        after: Vec<(Option<AccountId>, Option<AccountId>, u128)>,
+    }
+
+    impl PSP22Hooks for RecordingHooks {
+        fn _before_token_transfer(
+            &mut self,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            value: u128,
+        ) -> Result<(), PSP22Error> {
+            if self.blocked.is_some() && (from == self.blocked || to == self.blocked) {
+                return Err(PSP22Error::AccountRestricted);
+            }
+            self.before.push((from, to, value));
+            Ok(())
+        }
+
+        fn _after_token_transfer(
+            &mut self,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            value: u128,
+        ) -> Result<(), PSP22Error> {
+            self.after.push((from, to, value));
+            Ok(())
+        }
+    }
+
+    fn assert_supply_invariant(data: &PSP22Data) {
+        let sum: u128 = holders()
+            .iter()
+            .map(|holder| data.balance_of(*holder))
+            .sum();
+        assert_eq!(sum, data.total_supply(), "sum of balances != total_supply");
+    }
+
+    #[ink::test]
+    fn mint_keeps_invariant() {
+        let (alice, bob) = (accounts().alice, accounts().bob);
+        let mut data = PSP22Data::default();
+        assert_eq!(
+            data._mint_to(&mut (), alice, 100)
+                .map(|events| events.len()),
+            Ok(1)
+        );
+        assert_eq!(
+            data._mint_to(&mut (), bob, 50).map(|events| events.len()),
+            Ok(1)
+        );
+        assert_eq!(
+            data._mint_to(&mut (), bob, 0).map(|events| events.len()),
+            Ok(0)
+        );
+        assert_eq!(data.total_supply(), 150);
+        assert_supply_invariant(&data);
+
+        assert!(data._mint_to(&mut (), alice, u128::MAX).is_err());
+        assert_eq!(data.total_supply(), 150);
+        assert_supply_invariant(&data);
+    }
+
+    #[ink::test]
+    fn transfer_keeps_invariant() {
+        let (alice, bob, charlie) = (accounts().alice, accounts().bob, accounts().charlie);
+        let mut data = PSP22Data::default();
+        data._mint_to(&mut (), alice, 100).unwrap();
+
+        assert_eq!(
+            data.transfer(&mut (), alice, bob, 30)
+                .map(|events| events.len()),
+            Ok(1)
+        );
+        assert_eq!(
+            data.transfer(&mut (), bob, charlie, 30)
+                .map(|events| events.len()),
+            Ok(1)
+        );
+        assert_eq!(
+            data.transfer(&mut (), alice, alice, 10)
+                .map(|events| events.len()),
+            Ok(0)
+        );
+        assert_eq!(
+            data.transfer(&mut (), bob, alice, 1)
+                .map(|events| events.len()),
+            Err(PSP22Error::InsufficientBalance)
+        );
+        assert_eq!(data.balance_of(alice), 70);
+        assert_eq!(data.balance_of(bob), 0);
+        assert_eq!(data.balance_of(charlie), 30);
+        assert_supply_invariant(&data);
+    }
+
+    #[ink::test]
+    fn transfer_from_keeps_invariant() {
+        let (alice, bob, charlie) = (accounts().alice, accounts().bob, accounts().charlie);
+        let mut data = PSP22Data::default();
+        data._mint_to(&mut (), alice, 100).unwrap();
+        data.approve(alice, bob, 60).unwrap();
+
+        assert_eq!(
+            data.transfer_from(&mut (), bob, alice, charlie, 40)
+                .map(|events| events.len()),
+            Ok(2)
+        );
+        assert_eq!(data.allowance(alice, bob), 20);
+        assert_eq!(
+            data.transfer_from(&mut (), bob, alice, charlie, 21)
+                .map(|events| events.len()),
+            Err(PSP22Error::InsufficientAllowance)
+        );
+        assert_eq!(
+            data.transfer_from(&mut (), bob, alice, charlie, 20)
+                .map(|events| events.len()),
+            Ok(2)
+        );
+        assert_eq!(data.allowance(alice, bob), 0);
+        assert_eq!(data.balance_of(alice), 40);
+        assert_eq!(data.balance_of(charlie), 60);
+        assert_supply_invariant(&data);
+    }
+
+    #[ink::test]
+    fn transfer_fee_keeps_invariant() {
+        let (alice, bob, eve) = (accounts().alice, accounts().bob, accounts().eve);
+        let mut data = PSP22Data::default();
+        data._mint_to(&mut (), alice, 10_000).unwrap();
+
+        assert_eq!(data._set_transfer_fee(250, Some(eve)), Ok(()));
+        assert_eq!(data.transfer_fee_for(alice, bob, u128::MAX), u128::MAX / 40);
+        assert_eq!(
+            data.transfer(&mut (), alice, bob, 1_000)
+                .map(|events| events.len()),
+            Ok(2)
+        );
+        assert_eq!(data.balance_of(bob), 975);
+        assert_eq!(data.balance_of(eve), 25);
+        assert_supply_invariant(&data);
+
+        assert_eq!(data._set_transfer_fee(250, None), Ok(()));
+        data.transfer(&mut (), bob, alice, 400).unwrap();
+        assert_eq!(data.balance_of(alice), 9_390);
+        assert_eq!(data.total_supply(), 9_990);
+        assert_supply_invariant(&data);
+    }
+
+    #[ink::test]
+    fn hooks_run_around_every_balance_change() {
+        let (alice, bob, eve) = (accounts().alice, accounts().bob, accounts().eve);
+        let mut data = PSP22Data::default();
+        let mut hooks = RecordingHooks::default();
+        data._mint_to(&mut hooks, alice, 100).unwrap();
+        data._set_transfer_fee(1_000, Some(eve)).unwrap();
+        data.transfer(&mut hooks, alice, bob, 50).unwrap();
+        data._burn_from(&mut hooks, bob, 5).unwrap();
+
+        let expected = vec![
+            (None, Some(alice), 100),
+            (Some(alice), Some(bob), 45),
+            (Some(alice), Some(eve), 5),
+            (Some(bob), None, 5),
+        ];
+        assert_eq!(hooks.before, expected);
+        assert_eq!(hooks.after, expected);
+
+        hooks.blocked = Some(bob);
+        assert_eq!(
+            data.transfer(&mut hooks, alice, bob, 10)
+                .map(|events| events.len()),
+            Err(PSP22Error::AccountRestricted)
+        );
+        assert_eq!(data.balance_of(bob), 40);
+        assert_supply_invariant(&data);
+    }
+
+    #[ink::test]
+    fn locks_limit_spendable_balance() {
+        let (alice, bob) = (accounts().alice, accounts().bob);
+        let mut data = PSP22Data::default();
+        data._mint_to(&mut (), alice, 100).unwrap();
+        for unlock_at in 1..=MAX_LOCKS as u64 {
+            data.transfer_locked(&mut (), alice, bob, 1, unlock_at * 10)
+                .unwrap();
+        }
+        assert_eq!(
+            data.transfer_locked(&mut (), alice, bob, 1, 1_000)
+                .map(|events| events.len()),
+            Err(PSP22Error::TooManyLocks)
+        );
+
+        ink::env::test::set_block_timestamp::<DefaultEnvironment>(100);
+        assert_eq!(data.locked_balance_of(bob), MAX_LOCKS as u128 - 10);
+        assert_eq!(data.unlocked_balance_of(bob), 10);
+        assert_eq!(
+            data.transfer(&mut (), bob, alice, 11)
+                .map(|events| events.len()),
+            Err(PSP22Error::TokensLocked)
+        );
+        assert_eq!(
+            data.transfer_locked(&mut (), alice, bob, 5, 1_000)
+                .map(|events| events.len()),
+            Ok(1)
+        );
+        assert_eq!(data.locks_of(bob).len(), MAX_LOCKS - 9);
+
+        // A lock with the same sender and unlock time is merged, and can be rejected
+        data.transfer_locked(&mut (), alice, bob, 1, 1_000).unwrap();
+        assert_eq!(data.locks_of(bob).len(), MAX_LOCKS - 9);
+        assert_eq!(data._reject_lock(bob, alice, 1_000), Ok(6));
+        assert_eq!(
+            data._reject_lock(bob, alice, 1_000),
+            Err(PSP22Error::LockNotFound)
+        );
+        assert_eq!(data.locks_of(bob).len(), MAX_LOCKS - 10);
+        assert_supply_invariant(&data);
+    }
+
+    #[ink::test]
+    fn dividends_follow_balance_changes() {
+        let (alice, bob, charlie) = (accounts().alice, accounts().bob, accounts().charlie);
+        let contract = accounts().frank;
+        ink::env::test::set_callee::<DefaultEnvironment>(contract);
+        let mut data = PSP22Data::default();
+        assert_eq!(data._distribute(100), Err(PSP22Error::NoSupply));
+        // Tokens held by the contract earn nothing
+        data._mint_to(&mut (), contract, 100).unwrap();
+        assert_eq!(data._distribute(100), Err(PSP22Error::NoSupply));
+        data._mint_to(&mut (), alice, 100).unwrap();
+        assert_eq!(data._distribute(100), Ok(()));
+
+        data.transfer(&mut (), alice, bob, 50).unwrap();
+        data._mint_to(&mut (), charlie, 50).unwrap();
+        assert_eq!(data._distribute(300), Ok(()));
+        data._burn_from(&mut (), charlie, 50).unwrap();
+
+        assert_eq!(data.withdrawable_dividend_of(alice), 200);
+        assert_eq!(data.withdrawable_dividend_of(bob), 100);
+        assert_eq!(data.withdrawable_dividend_of(charlie), 100);
+        assert_eq!(data.withdrawable_dividend_of(contract), 0);
+        assert_eq!(data._claim(charlie), 100);
+        assert_eq!(data._claim(charlie), 0);
+        assert_eq!(data.dividend_reserve(), 300);
+    }
+
+    #[ink::test]
+    fn dividends_keep_precision_with_large_supplies() {
+        let (alice, bob, charlie) = (accounts().alice, accounts().bob, accounts().charlie);
+        ink::env::test::set_callee::<DefaultEnvironment>(accounts().frank);
+        let mut data = PSP22Data::default();
+        // One billion tokens with 18 decimals
+        data._mint_to(&mut (), alice, 600_000_000 * 10u128.pow(18))
+            .unwrap();
+        data._mint_to(&mut (), bob, 300_000_000 * 10u128.pow(18))
+            .unwrap();
+        data._mint_to(&mut (), charlie, 100_000_000 * 10u128.pow(18))
+            .unwrap();
+
+        // Each distribution is worth far less than one unit per share
+        for _ in 0..10 {
+            data._distribute(10u128.pow(9)).unwrap();
+        }
+        data.transfer(&mut (), alice, charlie, 10u128.pow(20))
+            .unwrap();
+        data._distribute(10u128.pow(17)).unwrap();
+
+        let expected = [
+            (
+                alice,
+                6 * 10u128.pow(16) - 10u128.pow(10) + 6 * 10u128.pow(9),
+            ),
+            (bob, 3 * 10u128.pow(16) + 3 * 10u128.pow(9)),
+            (charlie, 10u128.pow(16) + 10u128.pow(10) + 10u128.pow(9)),
+        ];
+        // Each credit rounds down by less than one unit
+        for (holder, dividend) in expected {
+            let withdrawable = data.withdrawable_dividend_of(holder);
+            assert!(withdrawable <= dividend && dividend - withdrawable <= 2);
+        }
+        let claimed: u128 = expected
+            .iter()
+            .map(|(holder, _)| data._claim(*holder))
+            .sum();
+        assert_eq!(
+            claimed + data.dividend_reserve(),
+            10u128.pow(17) + 10u128.pow(10)
+        );
+        assert!(data.dividend_reserve() <= 6);
+    }
+
+    #[ink::test]
+    fn elastic_supply_moves_whole_balances_without_dust() {
+        let (alice, bob) = (accounts().alice, accounts().bob);
+        let mut data = PSP22Data::default();
+        data._init_elastic();
+        data._mint_to(&mut (), alice, 100).unwrap();
+        data.transfer(&mut (), alice, bob, 30).unwrap();
+        assert_eq!(data._rebase(1_000), Ok(()));
+        assert_eq!(data.balance_of(alice), 700);
+        assert_eq!(data.balance_of(bob), 300);
+
+        assert_eq!(data._rebase(333), Ok(()));
+        let bob_balance = data.balance_of(bob);
+        data.transfer(&mut (), bob, alice, bob_balance).unwrap();
+        assert_eq!(data.shares_of(bob), 0);
+        assert_eq!(data.shares_of(alice), 100);
+        assert_eq!(data.balance_of(alice), 333);
+
+        data._burn_from(&mut (), alice, 333).unwrap();
+        assert_eq!(data.total_shares(), 0);
+        assert_eq!(data._rebase(10), Err(PSP22Error::InvalidRebase));
+    }
+
+    #[ink::test]
+    fn elastic_supply_rounds_against_the_account() {
+        let (alice, bob) = (accounts().alice, accounts().bob);
+        let mut data = PSP22Data::default();
+        data._init_elastic();
+        data._mint_to(&mut (), alice, 100).unwrap();
+        assert_eq!(data._rebase(1_000), Ok(()));
+
+        // Debits take whole shares, rounded up
+        data.transfer(&mut (), alice, bob, 15).unwrap();
+        assert_eq!(data.shares_of(alice), 98);
+        assert_eq!(data.shares_of(bob), 2);
+        data._burn_from(&mut (), alice, 1).unwrap();
+        assert_eq!(data.shares_of(alice), 97);
+        assert_eq!(data.total_shares(), 99);
+
+        // A mint worth less than one share is rejected
+        assert_eq!(
+            data._mint_to(&mut (), bob, 5).map(|events| events.len()),
+            Err(PSP22Error::ZeroShares)
+        );
+        assert_eq!(data.total_supply(), 999);
+        assert_eq!(data.shares_of(bob), 2);
+
+        // A partial debit cannot take the last share of a balance
+        assert_eq!(data.balance_of(bob), 20);
+        assert_eq!(
+            data._burn_from(&mut (), bob, 19).map(|events| events.len()),
+            Err(PSP22Error::RemainderTooSmall)
+        );
+        data._burn_from(&mut (), bob, 20).unwrap();
+        assert_eq!(data.total_shares(), 97);
+        assert_eq!(data.balance_of(alice), data.total_supply());
+
+        // Once every share is burned, shares are minted one to one again
+        data._burn_from(&mut (), alice, 979).unwrap();
+        assert_eq!(data.total_shares(), 0);
+        data._mint_to(&mut (), bob, 5).unwrap();
+        assert_eq!(data.shares_of(bob), 5);
+        assert_eq!(data.balance_of(bob), 5);
+    }
+
+    #[test]
+    fn mul_div_handles_wide_products() {
+        assert_eq!(mul_div(6, 7, 4), 10);
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), u128::MAX);
+        assert_eq!(mul_div(u128::MAX, 3, 6), u128::MAX / 2);
+        assert_eq!(mul_div(1 << 100, 1 << 100, 1 << 90), 1 << 110);
+        assert_eq!(mul_div(u128::MAX, 2, 1), u128::MAX);
+        assert_eq!(mul_div_up(6, 7, 4), 11);
+        assert_eq!(mul_div_up(6, 8, 4), 12);
+        assert_eq!(mul_div_up(u128::MAX, 3, 6), u128::MAX / 2 + 1);
+        assert_eq!(div_wide(1, 5, 2), ((1 << 127) + 2, 1));
+        assert_eq!(wide_mul(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+    }
+
+    #[ink::test]
+    fn burn_keeps_invariant() {
+        let (alice, bob) = (accounts().alice, accounts().bob);
+        let mut data = PSP22Data::default();
+        data._mint_to(&mut (), alice, 100).unwrap();
+        data.transfer(&mut (), alice, bob, 40).unwrap();
+
+        assert_eq!(
+            data._burn_from(&mut (), bob, 40).map(|events| events.len()),
+            Ok(1)
+        );
+        assert_eq!(
+            data._burn_from(&mut (), alice, 61)
+                .map(|events| events.len()),
+            Err(PSP22Error::InsufficientBalance)
+        );
+        assert_eq!(
+            data._burn_from(&mut (), alice, 60)
+                .map(|events| events.len()),
+            Ok(1)
+        );
+        assert_eq!(data.total_supply(), 0);
+        assert_supply_invariant(&data);
+    }
+
+    #[ink::test]
+    fn burn_from_spends_allowance() {
+        let (alice, bob) = (accounts().alice, accounts().bob);
+        let mut data = PSP22Data::default();
+        data._mint_to(&mut (), alice, 100).unwrap();
+        data.approve(alice, bob, 30).unwrap();
+
+        assert_eq!(
+            data.burn_from(&mut (), bob, alice, 31)
+                .map(|events| events.len()),
+            Err(PSP22Error::InsufficientAllowance)
+        );
+        assert_eq!(
+            data.burn_from(&mut (), bob, alice, 20)
+                .map(|events| events.len()),
+            Ok(2)
+        );
+        assert_eq!(data.allowance(alice, bob), 10);
+        assert_eq!(
+            data.burn_from(&mut (), alice, alice, 50)
+                .map(|events| events.len()),
+            Ok(1)
+        );
+        assert_eq!(data.balance_of(alice), 30);
+        assert_eq!(data.total_supply(), 30);
+        assert_supply_invariant(&data);
+    }
+
+    #[ink::test]
+    fn allowance_changes_work() {
+        let (alice, bob) = (accounts().alice, accounts().bob);
+        let mut data = PSP22Data::default();
+        assert_eq!(
+            data.approve(alice, alice, 10).map(|events| events.len()),
+            Ok(0)
+        );
+        data.approve(alice, bob, 10).unwrap();
+        data.increase_allowance(alice, bob, 5).unwrap();
+        assert_eq!(data.allowance(alice, bob), 15);
+        assert_eq!(
+            data.decrease_allowance(alice, bob, 16)
+                .map(|events| events.len()),
+            Err(PSP22Error::InsufficientAllowance)
+        );
+        data.decrease_allowance(alice, bob, 15).unwrap();
+        assert_eq!(data.allowance(alice, bob), 0);
+        assert_supply_invariant(&data);
+    }
+
+    #[ink::test]
+    fn snapshots_record_past_values() {
+        let (alice, bob) = (accounts().alice, accounts().bob);
+        let mut data = PSP22Data::default();
+        data._mint_to(&mut (), alice, 100).unwrap();
+        assert_eq!(
+            data.balance_of_at(alice, 1),
+            Err(PSP22Error::SnapshotNotFound)
+        );
+
+        let first = data._snapshot();
+        data.transfer(&mut (), alice, bob, 30).unwrap();
+        data._burn_from(&mut (), bob, 10).unwrap();
+        let second = data._snapshot();
+        let third = data._snapshot();
+        data._mint_to(&mut (), bob, 50).unwrap();
+        data.transfer(&mut (), alice, bob, 20).unwrap();
+
+        assert_eq!(data.balance_of_at(alice, first), Ok(100));
+        assert_eq!(data.balance_of_at(bob, first), Ok(0));
+        assert_eq!(data.total_supply_at(first), Ok(100));
+        assert_eq!(data.balance_of_at(alice, second), Ok(70));
+        assert_eq!(data.balance_of_at(bob, second), Ok(20));
+        assert_eq!(data.total_supply_at(second), Ok(90));
+        assert_eq!(data.balance_of_at(bob, third), Ok(20));
+        assert_eq!(data.total_supply_at(third), Ok(90));
+        assert_eq!(data.balance_of(bob), 90);
+        assert_eq!(data.total_supply(), 140);
+        assert_eq!(
+            data.balance_of_at(alice, 4),
+            Err(PSP22Error::SnapshotNotFound)
+        );
+        assert_supply_invariant(&data);
     }
 }
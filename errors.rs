@@ -49,6 +49,32 @@ pub enum PSP22Error {
     OwnableError(OwnableError),
     SafeTransferCheckFailed(String),
     AccessControlError(AccessControlError),
+    Paused,
+    NotPaused,
+    PermitExpired,
+    PermitInvalidSignature,
+    GenesisHashAlreadySet,
+    SnapshotNotFound,
+    FutureLookup,
+    InsufficientCollateral,
+    UnderlyingNotSet,
+    AccountRestricted,
+    InvalidTransferFee,
+    InvalidVestingSchedule,
+    VestingScheduleNotFound,
+    VestingNotRevocable,
+    TokensLocked,
+    TooManyLocks,
+    LockNotFound,
+    NoSupply,
+    InvalidRewardAsset,
+    NotElastic,
+    InvalidRebase,
+    ZeroShares,
+    RemainderTooSmall,
+    ElasticUnsupported,
+    NotWrappedNative,
+    MintingDisabled,
 }
 
 impl From<AccessControlError> for PSP22Error {
@@ -63,12 +89,49 @@ impl From<OwnableError> for PSP22Error {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum PSP22ReceiverError {
+    TransferRejected(String),
+}
+
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum FlashLenderError {
+    Custom(String),
+    WrongTokenAddress,
+    InvalidFee,
+    AllowanceDoesNotAllowRefund,
+    BorrowerRejected(String),
+    PSP22Error(PSP22Error),
+    OwnableError(OwnableError),
+}
+
+impl From<PSP22Error> for FlashLenderError {
+    fn from(error: PSP22Error) -> Self {
+        FlashLenderError::PSP22Error(error)
+    }
+}
+
+impl From<OwnableError> for FlashLenderError {
+    fn from(error: OwnableError) -> Self {
+        FlashLenderError::OwnableError(error)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum FlashBorrowerError {
+    FlashloanRejected(String),
+}
+
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum OwnableError {
     Custom(String),
     CallerIsNotOwner,
     NewOwnerIsNotSet,
+    CallerIsNotPendingOwner,
 }
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -77,6 +140,11 @@ pub enum UpgradeableError {
     Custom(String),
     SetCodeHashFailed,
     OwnableError(OwnableError),
+    InvalidDelay,
+    EtaTooEarly,
+    UpgradeNotReady,
+    NoPendingUpgrade,
+    AlreadyMigrated,
 }
 
 impl From<OwnableError> for UpgradeableError {
@@ -0,0 +1,42 @@
+use crate::data::mul_div;
+use crate::errors::FlashLenderError;
+use ink::{primitives::AccountId, storage::Lazy};
+
+/// Upper bound of the flash loan fee, in basis points.
+pub const MAX_FLASH_FEE: u16 = 10_000;
+
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct FlashLenderData {
+    fee: Lazy<u16>,
+    fee_recipient: Lazy<Option<AccountId>>,
+}
+
+impl FlashLenderData {
+    /// Flash loan fee in basis points.
+    pub fn fee(&self) -> u16 {
+        self.fee.get().unwrap_or_default()
+    }
+
+    /// Receiver of flash loan fees, fees are burned when `None`.
+    pub fn fee_recipient(&self) -> Option<AccountId> {
+        self.fee_recipient.get().flatten()
+    }
+
+    pub fn flash_fee(&self, amount: u128) -> u128 {
+        mul_div(amount, self.fee() as u128, MAX_FLASH_FEE as u128)
+    }
+
+    pub fn _set_fee(
+        &mut self,
+        fee: u16,
+        fee_recipient: Option<AccountId>,
+    ) -> Result<(), FlashLenderError> {
+        if fee > MAX_FLASH_FEE {
+            return Err(FlashLenderError::InvalidFee);
+        }
+        self.fee.set(&fee);
+        self.fee_recipient.set(&fee_recipient);
+        Ok(())
+    }
+}
@@ -2,78 +2,228 @@
 
 mod access_control;
 mod capped;
+mod compliance;
 mod data;
 mod errors;
+mod flashloan;
 mod metadata;
+mod migration;
 mod owner;
+mod pausable;
+mod permit;
 mod traits;
+mod upgradeable;
+mod vesting;
+mod votes;
+mod wrapped_native;
+mod wrapper;
 
 pub use access_control::AccessControlData;
 pub use capped::Capped;
-pub use data::{PSP22Data, PSP22Event};
-pub use errors::{AccessControlError, Error, OwnableError, PSP22Error, UpgradeableError};
+pub use compliance::ComplianceData;
+pub use data::{
+    DividendPerShare, Lock, PSP22Data, PSP22Event, PSP22Hooks, MAX_LOCKS, MAX_TRANSFER_FEE,
+};
+pub use errors::{
+    AccessControlError, Error, FlashBorrowerError, FlashLenderError, OwnableError, PSP22Error,
+    PSP22ReceiverError, UpgradeableError,
+};
+pub use flashloan::FlashLenderData;
 pub use metadata::Metadata;
+pub use migration::{MigrationData, RootV0, RootV1, STORAGE_VERSION};
 pub use owner::OwnableData;
+pub use pausable::PausableData;
+pub use permit::PermitData;
 pub use traits::{
-    AccessControl, AdminTrait, Ownable, PSP22Burnable, PSP22Capped, PSP22Metadata, PSP22Mintable,
-    RoleType, UpgradeableTrait, PSP22,
+    AccessControl, AccessControlEnumerable, AdminTrait, Ownable, PSP22Batch, PSP22Burnable,
+    PSP22Capped, PSP22Compliance, PSP22Dividends, PSP22FlashBorrower, PSP22FlashLender,
+    PSP22Lockable, PSP22Metadata, PSP22MetadataAdmin, PSP22Mintable, PSP22Permit, PSP22Rebase,
+    PSP22Receiver, PSP22Snapshot, PSP22TransferFee, PSP22Vesting, PSP22Votes, PSP22WrappedNative,
+    PSP22Wrapper, Pausable, RoleType, UpgradeableTrait, PSP22,
 };
+pub use upgradeable::{UpgradeableData, MIN_UPGRADE_DELAY};
+pub use vesting::{VestingData, VestingSchedule};
+pub use votes::{VotesData, VotesEvent};
+pub use wrapped_native::WrappedNativeData;
+pub use wrapper::WrapperData;
 
 #[ink::contract]
 pub mod psp22_standard {
     use crate::{
-        AccessControl, AccessControlData, AccessControlError, AdminTrait, Capped, Metadata,
-        Ownable, OwnableData, OwnableError, PSP22Burnable, PSP22Capped, PSP22Data, PSP22Error,
-        PSP22Event, PSP22Metadata, PSP22Mintable, RoleType, UpgradeableError, UpgradeableTrait,
-        PSP22, Error
+        AccessControl, AccessControlData, AccessControlEnumerable, AccessControlError, AdminTrait,
+        Capped, ComplianceData, Error, FlashBorrowerError, FlashLenderData, FlashLenderError, Lock,
+        Metadata, MigrationData, Ownable, OwnableData, OwnableError, PSP22Batch, PSP22Burnable,
+        PSP22Capped, PSP22Compliance, PSP22Data, PSP22Dividends, PSP22Error, PSP22Event,
+        PSP22FlashLender, PSP22Hooks, PSP22Lockable, PSP22Metadata, PSP22MetadataAdmin,
+        PSP22Mintable, PSP22Permit, PSP22Rebase, PSP22ReceiverError, PSP22Snapshot,
+        PSP22TransferFee, PSP22Vesting, PSP22Votes, PSP22WrappedNative, PSP22Wrapper, Pausable,
+        PausableData, PermitData, RoleType, RootV0, RootV1, UpgradeableData, UpgradeableError,
+        UpgradeableTrait, VestingData, VestingSchedule, VotesData, VotesEvent, WrappedNativeData,
+        WrapperData, MIN_UPGRADE_DELAY, PSP22, STORAGE_VERSION,
     };
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::env::hash::{Blake2x256, HashOutput};
+    use ink::env::CallFlags;
     use ink::prelude::{string::String, vec::Vec};
 
     // MINTER RoleType = 4254773782
     pub const MINTER: RoleType = ink::selector_id!("MINTER");
+    // PAUSER RoleType = 1290383334
+    pub const PAUSER: RoleType = ink::selector_id!("PAUSER");
+    // SNAPSHOT RoleType = 2495962491
+    pub const SNAPSHOT: RoleType = ink::selector_id!("SNAPSHOT");
+    // CAP_MANAGER RoleType = 3107171462
+    pub const CAP_MANAGER: RoleType = ink::selector_id!("CAP_MANAGER");
+    // METADATA_ADMIN RoleType = 4178033462
+    pub const METADATA_ADMIN: RoleType = ink::selector_id!("METADATA_ADMIN");
+    // COMPLIANCE RoleType = 1908091123
+    pub const COMPLIANCE: RoleType = ink::selector_id!("COMPLIANCE");
+    // VESTING_ADMIN RoleType = 3452065303
+    pub const VESTING_ADMIN: RoleType = ink::selector_id!("VESTING_ADMIN");
+    // REBASER RoleType = 320437839
+    pub const REBASER: RoleType = ink::selector_id!("REBASER");
+    // FORCE_BURNER RoleType = 2769390455
+    pub const FORCE_BURNER: RoleType = ink::selector_id!("FORCE_BURNER");
+
+    pub const PERMIT_DOMAIN: &[u8] = b"PSP22Permit";
+
+    /// Pause, compliance and votes extensions run around every balance change.
+    struct TokenHooks<'a> {
+        pausable: &'a PausableData,
+        compliance: Option<&'a ComplianceData>,
+        /// Caller moving the tokens, screened along with the holders.
+        operator: AccountId,
+        /// `None` with an elastic supply, which does not track votes.
+        votes: Option<&'a mut VotesData>,
+        block: BlockNumber,
+        votes_events: Vec<VotesEvent>,
+    }
+
+    impl PSP22Hooks for TokenHooks<'_> {
+        fn _before_token_transfer(
+            &mut self,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            _value: u128,
+        ) -> Result<(), PSP22Error> {
+            self.pausable._check_not_paused()?;
+            if let Some(compliance) = self.compliance {
+                for account in [from, to, Some(self.operator)].into_iter().flatten() {
+                    compliance._check_account(account)?;
+                }
+            }
+            Ok(())
+        }
+
+        fn _after_token_transfer(
+            &mut self,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            value: u128,
+        ) -> Result<(), PSP22Error> {
+            if let Some(votes) = self.votes.as_deref_mut() {
+                let events = votes._transfer_voting_units(from, to, value, self.block);
+                self.votes_events.extend(events);
+            }
+            Ok(())
+        }
+    }
 
+    /// Storage items only hold `Lazy` and `Mapping` fields, keeping the root cell empty.
     #[ink(storage)]
+    #[derive(Default)]
     pub struct Psp22Standard {
         data: PSP22Data,
         metadata: Metadata,
         ownable: OwnableData,
         cap: Capped,
         admin: AccessControlData,
-    }
-
-    impl Default for Psp22Standard {
-        fn default() -> Self {
-            Self {
-                data: Default::default(),
-                metadata: Default::default(),
-                ownable: Default::default(),
-                cap: Default::default(),
-                admin: Default::default(),
-            }
-        }
+        pausable: PausableData,
+        permit: PermitData,
+        votes: VotesData,
+        wrapped_native: WrappedNativeData,
+        wrapper: WrapperData,
+        flash_lender: FlashLenderData,
+        upgradeable: UpgradeableData,
+        migration: MigrationData,
+        compliance: ComplianceData,
+        vesting: VestingData,
     }
 
     impl Psp22Standard {
+        /// Deploys the token. `genesis_hash` identifies the target chain in the permit domain.
         #[ink(constructor)]
         pub fn new(
             cap: Balance,
             name: Option<String>,
             symbol: Option<String>,
             decimals: u8,
+            genesis_hash: Hash,
         ) -> Self {
             let mut instance = Self::default();
             instance.ownable._init_with_owner(Self::env().caller());
+            assert!(instance.upgradeable._init_delay(MIN_UPGRADE_DELAY).is_ok());
+            instance.migration._set_version(STORAGE_VERSION);
             assert!(instance.cap._init_cap(cap).is_ok());
-            instance.metadata.name = name;
-            instance.metadata.symbol = symbol;
-            instance.metadata.decimals = decimals;
+            instance.metadata._set_name(name);
+            instance.metadata._set_symbol(symbol);
+            instance.metadata._set_decimals(decimals);
+            instance.permit._init_genesis_hash(genesis_hash);
             instance.admin._init_with_admin(Some(Self::env().caller()));
+            Self::env().emit_event(RoleGranted {
+                role: AccessControlData::_default_admin(),
+                grantee: Some(Self::env().caller()),
+                grantor: None,
+            });
+            instance
+        }
+
+        /// Deploys a wrapper of the PSP22 token at `underlying`.
+        #[ink(constructor)]
+        pub fn new_wrapper(
+            cap: Balance,
+            name: Option<String>,
+            symbol: Option<String>,
+            decimals: u8,
+            genesis_hash: Hash,
+            underlying: AccountId,
+        ) -> Self {
+            let mut instance = Self::new(cap, name, symbol, decimals, genesis_hash);
+            instance.wrapper._init_underlying(underlying);
+            instance
+        }
+
+        /// Deploys a wrapped native token, minted only against native deposits.
+        #[ink(constructor)]
+        pub fn new_wrapped_native(
+            cap: Balance,
+            name: Option<String>,
+            symbol: Option<String>,
+            decimals: u8,
+            genesis_hash: Hash,
+        ) -> Self {
+            let mut instance = Self::new(cap, name, symbol, decimals, genesis_hash);
+            instance.wrapped_native._init_enabled();
+            instance
+        }
+
+        /// Deploys a token with an elastic supply that `REBASER` can rebase.
+        #[ink(constructor)]
+        pub fn new_elastic(
+            cap: Balance,
+            name: Option<String>,
+            symbol: Option<String>,
+            decimals: u8,
+            genesis_hash: Hash,
+        ) -> Self {
+            let mut instance = Self::new(cap, name, symbol, decimals, genesis_hash);
+            instance.data._init_elastic();
             instance
         }
 
-        fn emit_events(&self, events: Vec<PSP22Event>) {
+        fn emit_events(&self, events: &[PSP22Event]) {
             for event in events {
-                match event {
+                match *event {
                     PSP22Event::Transfer { from, to, value } => {
                         self.env().emit_event(Transfer { from, to, value })
                     }
@@ -89,6 +239,326 @@ pub mod psp22_standard {
                 }
             }
         }
+
+        fn emit_votes_events(&self, events: Vec<VotesEvent>) {
+            for event in events {
+                match event {
+                    VotesEvent::DelegateChanged {
+                        delegator,
+                        from_delegate,
+                        to_delegate,
+                    } => self.env().emit_event(DelegateChanged {
+                        delegator,
+                        from_delegate,
+                        to_delegate,
+                    }),
+                    VotesEvent::DelegateVotesChanged {
+                        delegate,
+                        previous_votes,
+                        new_votes,
+                    } => self.env().emit_event(DelegateVotesChanged {
+                        delegate,
+                        previous_votes,
+                        new_votes,
+                    }),
+                }
+            }
+        }
+
+        /// Panics until `migrate` has brought storage up to `STORAGE_VERSION`.
+        fn _ensure_migrated(&self) {
+            assert!(self.migration._is_migrated(), "storage migration required");
+        }
+
+        /// Backed tokens are only minted against collateral, anything else would let its
+        /// holders redeem collateral deposited by others.
+        fn _check_mintable(&self) -> Result<(), PSP22Error> {
+            if self.wrapped_native.enabled() || self.wrapper.underlying().is_some() {
+                return Err(PSP22Error::MintingDisabled);
+            }
+            Ok(())
+        }
+
+        /// Upgrades storage written by version `version - 1` of the layout, `0` being the
+        /// unversioned layout of the first deployments.
+        fn _migration_step(&mut self, version: u32) {
+            let root_key = <Self as ink::storage::traits::StorageKey>::KEY;
+            match version {
+                // Repacks the unversioned root in the version 1 layout for the next step
+                1 => match ink::env::get_contract_storage::<_, RootV0>(&root_key) {
+                    Ok(Some(root)) => {
+                        // Votes were not tracked yet, past lookups start from the migration
+                        self.votes
+                            ._restore_total_supply(root.total_supply, self.env().block_number());
+                        ink::env::set_contract_storage(&root_key, &RootV1::from(root));
+                    }
+                    Ok(None) => {}
+                    Err(_) => panic!("root cell does not match the unversioned layout"),
+                },
+                2 => {
+                    match ink::env::get_contract_storage::<_, RootV1>(&root_key) {
+                        Ok(Some(root)) => self._restore_root_v1(root),
+                        // Nothing was packed into the root cell
+                        Ok(None) => {}
+                        Err(_) => panic!("root cell does not match storage version 1"),
+                    }
+                }
+                // Compliance lists start empty, existing holders stay unrestricted
+                3 => self.compliance._set_allowlist_mode(false),
+                // Transfers stay fee free until the owner sets a fee
+                4 => assert!(self.data._set_transfer_fee(0, None).is_ok()),
+                // Vesting schedules start out empty, the schedule counter reads as zero
+                5 => {}
+                // Locks start out empty, every existing balance is unlocked
+                6 => {}
+                // Nothing is distributed yet, dividends default to the native currency
+                7 => assert!(self.data._set_reward_token(None).is_ok()),
+                // Existing supplies are not elastic, every balance holds as many shares
+                8 => self.data._restore_total_shares(),
+                _ => unreachable!("no migration step for storage version {}", version),
+            }
+        }
+
+        /// Moves the fields packed into the version 1 root cell to their own cells.
+        fn _restore_root_v1(&mut self, root: RootV1) {
+            self.data
+                ._restore(root.total_supply, root.current_snapshot_id);
+            self.metadata._set_name(root.name);
+            self.metadata._set_symbol(root.symbol);
+            self.metadata._set_decimals(root.decimals);
+            self.ownable._restore(root.owner, root.pending_owner);
+            assert!(self.cap._init_cap(root.cap).is_ok());
+            if root.paused {
+                assert!(self.pausable._pause().is_ok());
+            }
+            // Version 1 had no deployment modes, a token holding native collateral wraps it
+            if root.collateral > 0 {
+                self.wrapped_native._init_enabled();
+                self.wrapped_native._deposit(root.collateral);
+            }
+            if let Some(underlying) = root.underlying {
+                self.wrapper._init_underlying(underlying);
+                self.wrapper._deposit(root.deposited);
+            }
+            assert!(self
+                .flash_lender
+                ._set_fee(root.flash_fee, root.flash_fee_recipient)
+                .is_ok());
+            self.upgradeable
+                ._restore(root.upgrade_delay, root.pending_upgrade);
+        }
+
+        /// Runs `update` on the token data with the hooks of this contract, then emits the
+        /// resulting events.
+        fn _update_balances<F>(&mut self, update: F) -> Result<(), PSP22Error>
+        where
+            F: FnOnce(&mut PSP22Data, &mut TokenHooks) -> Result<Vec<PSP22Event>, PSP22Error>,
+        {
+            self._run_update(true, update)
+        }
+
+        /// Same as `_update_balances` without compliance checks, so restricted funds can be
+        /// seized.
+        fn _force_update_balances<F>(&mut self, update: F) -> Result<(), PSP22Error>
+        where
+            F: FnOnce(&mut PSP22Data, &mut TokenHooks) -> Result<Vec<PSP22Event>, PSP22Error>,
+        {
+            self._run_update(false, update)
+        }
+
+        fn _run_update<F>(&mut self, enforce_compliance: bool, update: F) -> Result<(), PSP22Error>
+        where
+            F: FnOnce(&mut PSP22Data, &mut TokenHooks) -> Result<Vec<PSP22Event>, PSP22Error>,
+        {
+            let block = self.env().block_number();
+            let mut hooks = TokenHooks {
+                pausable: &self.pausable,
+                compliance: enforce_compliance.then_some(&self.compliance),
+                operator: self.env().caller(),
+                votes: (!self.data.elastic()).then_some(&mut self.votes),
+                block,
+                votes_events: vec![],
+            };
+            let events = update(&mut self.data, &mut hooks)?;
+            let votes_events = hooks.votes_events;
+            self.emit_events(&events);
+            self.emit_votes_events(votes_events);
+            Ok(())
+        }
+
+        /// Restricted accounts can neither grant nor be granted allowances, revoking them is
+        /// always possible.
+        fn _check_approval(&self, owner: AccountId, spender: AccountId) -> Result<(), PSP22Error> {
+            self.compliance._check_account(owner)?;
+            self.compliance._check_account(spender)
+        }
+
+        /// Hash signed by `owner` to authorize a `PSP22Permit::permit` call.
+        fn _permit_message_hash(
+            &self,
+            owner: AccountId,
+            spender: AccountId,
+            value: u128,
+            nonce: u64,
+            deadline: u64,
+        ) -> [u8; 32] {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(
+                &(
+                    self.domain_separator(),
+                    owner,
+                    spender,
+                    value,
+                    nonce,
+                    deadline,
+                ),
+                &mut output,
+            );
+            output
+        }
+
+        /// Calls `PSP22Receiver::before_received` on `to` if it is a contract.
+        /// Plain accounts and no-op transfers are not checked.
+        fn _do_safe_transfer_check(
+            &self,
+            from: AccountId,
+            to: AccountId,
+            value: u128,
+            data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            if from == to || value == 0 || !self.env().is_contract(&to) {
+                return Ok(());
+            }
+            let result = build_call::<Environment>()
+                .call(to)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "PSP22Receiver::before_received"
+                    )))
+                    .push_arg(self.env().caller())
+                    .push_arg(from)
+                    .push_arg(value)
+                    .push_arg(data),
+                )
+                .returns::<Result<(), PSP22ReceiverError>>()
+                .try_invoke();
+            match result {
+                Ok(Ok(Ok(()))) => Ok(()),
+                Ok(Ok(Err(PSP22ReceiverError::TransferRejected(reason)))) => {
+                    Err(PSP22Error::SafeTransferCheckFailed(reason))
+                }
+                // The recipient contract does not implement `PSP22Receiver`
+                Ok(Err(ink::LangError::CouldNotReadInput)) => Ok(()),
+                _ => Err(PSP22Error::SafeTransferCheckFailed(String::from(
+                    "Error during call to receiver",
+                ))),
+            }
+        }
+
+        /// Calls `PSP22::transfer_from` on the `underlying` token.
+        fn _underlying_transfer_from(
+            &self,
+            underlying: AccountId,
+            from: AccountId,
+            to: AccountId,
+            value: u128,
+        ) -> Result<(), PSP22Error> {
+            build_call::<Environment>()
+                .call(underlying)
+                .call_flags(CallFlags::default().set_allow_reentry(true))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "PSP22::transfer_from"
+                    )))
+                    .push_arg(from)
+                    .push_arg(to)
+                    .push_arg(value)
+                    .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke()
+                .map_err(|_| PSP22Error::Custom(String::from("Underlying call failed")))?
+                .map_err(|_| PSP22Error::Custom(String::from("Underlying call failed")))?
+        }
+
+        /// Calls `PSP22::transfer` on the `underlying` token.
+        fn _underlying_transfer(
+            &self,
+            underlying: AccountId,
+            to: AccountId,
+            value: u128,
+        ) -> Result<(), PSP22Error> {
+            build_call::<Environment>()
+                .call(underlying)
+                .call_flags(CallFlags::default().set_allow_reentry(true))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer")))
+                        .push_arg(to)
+                        .push_arg(value)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke()
+                .map_err(|_| PSP22Error::Custom(String::from("Underlying call failed")))?
+                .map_err(|_| PSP22Error::Custom(String::from("Underlying call failed")))?
+        }
+
+        /// Calls `PSP22::balance_of` on the `underlying` token.
+        fn _underlying_balance_of(
+            &self,
+            underlying: AccountId,
+            owner: AccountId,
+        ) -> Result<u128, PSP22Error> {
+            build_call::<Environment>()
+                .call(underlying)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::balance_of")))
+                        .push_arg(owner),
+                )
+                .returns::<u128>()
+                .try_invoke()
+                .map_err(|_| PSP22Error::Custom(String::from("Underlying call failed")))?
+                .map_err(|_| PSP22Error::Custom(String::from("Underlying call failed")))
+        }
+
+        /// Calls `PSP22FlashBorrower::on_flashloan` on `receiver`.
+        fn _on_flashloan(
+            &self,
+            receiver: AccountId,
+            amount: u128,
+            fee: u128,
+            data: Vec<u8>,
+        ) -> Result<(), FlashLenderError> {
+            if !self.env().is_contract(&receiver) {
+                return Err(FlashLenderError::BorrowerRejected(String::from(
+                    "Receiver is not a contract",
+                )));
+            }
+            let result = build_call::<Environment>()
+                .call(receiver)
+                .call_flags(CallFlags::default().set_allow_reentry(true))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "PSP22FlashBorrower::on_flashloan"
+                    )))
+                    .push_arg(self.env().caller())
+                    .push_arg(self.env().account_id())
+                    .push_arg(amount)
+                    .push_arg(fee)
+                    .push_arg(data),
+                )
+                .returns::<Result<(), FlashBorrowerError>>()
+                .try_invoke();
+            match result {
+                Ok(Ok(Ok(()))) => Ok(()),
+                Ok(Ok(Err(FlashBorrowerError::FlashloanRejected(reason)))) => {
+                    Err(FlashLenderError::BorrowerRejected(reason))
+                }
+                _ => Err(FlashLenderError::BorrowerRejected(String::from(
+                    "Error during call to receiver",
+                ))),
+            }
+        }
     }
 
     #[ink(event)]
@@ -117,19 +587,204 @@ pub mod psp22_standard {
         new_owner: Option<AccountId>,
     }
 
+    #[ink(event)]
+    pub struct OwnershipTransferStarted {
+        #[ink(topic)]
+        old_owner: Option<AccountId>,
+        #[ink(topic)]
+        new_owner: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct UpgradeScheduled {
+        #[ink(topic)]
+        code_hash: Hash,
+        eta: u64,
+    }
+
+    #[ink(event)]
+    pub struct UpgradeExecuted {
+        #[ink(topic)]
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct UpgradeCancelled {
+        #[ink(topic)]
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct Migrated {
+        from_version: u32,
+        to_version: u32,
+    }
+
+    #[ink(event)]
+    pub struct AccountBlocked {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AccountUnblocked {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AccountAllowed {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AccountDisallowed {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AllowlistModeChanged {
+        enabled: bool,
+    }
+
+    #[ink(event)]
+    pub struct VestingCreated {
+        #[ink(topic)]
+        schedule_id: u32,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        amount: u128,
+    }
+
+    #[ink(event)]
+    pub struct VestingReleased {
+        #[ink(topic)]
+        schedule_id: u32,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        amount: u128,
+    }
+
+    #[ink(event)]
+    pub struct VestingRevoked {
+        #[ink(topic)]
+        schedule_id: u32,
+        unvested: u128,
+    }
+
+    #[ink(event)]
+    pub struct DividendsDistributed {
+        #[ink(topic)]
+        from: AccountId,
+        value: u128,
+    }
+
+    #[ink(event)]
+    pub struct DividendClaimed {
+        #[ink(topic)]
+        account: AccountId,
+        value: u128,
+    }
+
+    #[ink(event)]
+    pub struct Rebase {
+        previous_total_supply: u128,
+        new_total_supply: u128,
+    }
+
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: RoleType,
+        #[ink(topic)]
+        grantee: Option<AccountId>,
+        #[ink(topic)]
+        grantor: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: RoleType,
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        sender: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoleAdminChanged {
+        #[ink(topic)]
+        role: RoleType,
+        previous_admin: RoleType,
+        new_admin: RoleType,
+    }
+
+    #[ink(event)]
+    pub struct Snapshot {
+        id: u32,
+    }
+
+    #[ink(event)]
+    pub struct DelegateChanged {
+        #[ink(topic)]
+        delegator: AccountId,
+        #[ink(topic)]
+        from_delegate: Option<AccountId>,
+        #[ink(topic)]
+        to_delegate: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct DelegateVotesChanged {
+        #[ink(topic)]
+        delegate: AccountId,
+        previous_votes: u128,
+        new_votes: u128,
+    }
+
+    #[ink(event)]
+    pub struct CapChanged {
+        old: Option<Balance>,
+        new: Option<Balance>,
+    }
+
+    #[ink(event)]
+    pub struct MetadataUpdated {
+        key: String,
+        value: Option<String>,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
     impl PSP22 for Psp22Standard {
         #[ink(message)]
         fn total_supply(&self) -> u128 {
+            self._ensure_migrated();
             self.data.total_supply()
         }
 
         #[ink(message)]
         fn balance_of(&self, owner: AccountId) -> u128 {
+            self._ensure_migrated();
             self.data.balance_of(owner)
         }
 
         #[ink(message)]
         fn allowance(&self, owner: AccountId, spender: AccountId) -> u128 {
+            self._ensure_migrated();
             self.data.allowance(owner, spender)
         }
 
@@ -138,10 +793,12 @@ pub mod psp22_standard {
             &mut self,
             to: AccountId,
             value: u128,
-            _data: Vec<u8>,
+            data: Vec<u8>,
         ) -> Result<(), PSP22Error> {
-            let events = self.data.transfer(self.env().caller(), to, value)?;
-            self.emit_events(events);
+            self._ensure_migrated();
+            self._do_safe_transfer_check(self.env().caller(), to, value, data)?;
+            let caller = self.env().caller();
+            self._update_balances(|data, hooks| data.transfer(hooks, caller, to, value))?;
             Ok(())
         }
 
@@ -151,19 +808,25 @@ pub mod psp22_standard {
             from: AccountId,
             to: AccountId,
             value: u128,
-            _data: Vec<u8>,
+            data: Vec<u8>,
         ) -> Result<(), PSP22Error> {
-            let events = self
-                .data
-                .transfer_from(self.env().caller(), from, to, value)?;
-            self.emit_events(events);
+            self._ensure_migrated();
+            self._do_safe_transfer_check(from, to, value, data)?;
+            let caller = self.env().caller();
+            self._update_balances(|data, hooks| {
+                data.transfer_from(hooks, caller, from, to, value)
+            })?;
             Ok(())
         }
 
         #[ink(message)]
         fn approve(&mut self, spender: AccountId, value: u128) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            if value > 0 {
+                self._check_approval(self.env().caller(), spender)?;
+            }
             let events = self.data.approve(self.env().caller(), spender, value)?;
-            self.emit_events(events);
+            self.emit_events(&events);
             Ok(())
         }
 
@@ -173,10 +836,12 @@ pub mod psp22_standard {
             spender: AccountId,
             delta_value: u128,
         ) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self._check_approval(self.env().caller(), spender)?;
             let events = self
                 .data
                 .increase_allowance(self.env().caller(), spender, delta_value)?;
-            self.emit_events(events);
+            self.emit_events(&events);
             Ok(())
         }
 
@@ -186,59 +851,325 @@ pub mod psp22_standard {
             spender: AccountId,
             delta_value: u128,
         ) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
             let events = self
                 .data
                 .decrease_allowance(self.env().caller(), spender, delta_value)?;
-            self.emit_events(events);
+            self.emit_events(&events);
             Ok(())
         }
     }
 
-    impl PSP22Metadata for Psp22Standard {
+    impl PSP22Snapshot for Psp22Standard {
         #[ink(message)]
-        fn token_name(&self) -> Option<String> {
-            self.metadata.token_name()
+        fn snapshot(&mut self) -> Result<u32, PSP22Error> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(SNAPSHOT, Some(Self::env().caller()))?;
+            self.data._check_not_elastic()?;
+            let id = self.data._snapshot();
+            self.env().emit_event(Snapshot { id });
+            Ok(id)
         }
+
         #[ink(message)]
-        fn token_symbol(&self) -> Option<String> {
-            self.metadata.token_symbol()
+        fn balance_of_at(&self, account: AccountId, snapshot_id: u32) -> Result<u128, PSP22Error> {
+            self._ensure_migrated();
+            self.data.balance_of_at(account, snapshot_id)
         }
+
         #[ink(message)]
-        fn token_decimals(&self) -> u8 {
-            self.metadata.token_decimals()
+        fn total_supply_at(&self, snapshot_id: u32) -> Result<u128, PSP22Error> {
+            self._ensure_migrated();
+            self.data.total_supply_at(snapshot_id)
         }
     }
 
-    impl PSP22Mintable for Psp22Standard {
+    impl PSP22Votes for Psp22Standard {
         #[ink(message)]
-        fn mint(&mut self, to: AccountId, value: u128) -> Result<(), PSP22Error> {
-            self.admin._check_role(MINTER, Some(Self::env().caller()))?;
-            if self.data.total_supply() + value > self.cap.cap() {
-                return Err(PSP22Error::CapExceeded);
-            }
-            let events = self.data._mint_to(to, value)?;
-            self.emit_events(events);
-            Ok(())
+        fn delegates(&self, account: AccountId) -> Option<AccountId> {
+            self._ensure_migrated();
+            self.votes.delegates(account)
         }
-    }
 
-    impl PSP22Burnable for Psp22Standard {
         #[ink(message)]
-        fn burn(&mut self, from: AccountId, value: u128) -> Result<(), PSP22Error> {
-            self.ownable._check_owner(Some(self.env().caller()))?;
-            let events = self.data._burn_from(from, value)?;
-            self.emit_events(events);
-            Ok(())
+        fn get_votes(&self, account: AccountId) -> u128 {
+            self._ensure_migrated();
+            self.votes.get_votes(account)
         }
-    }
 
-    impl Ownable for Psp22Standard {
         #[ink(message)]
-        fn owner(&self) -> Option<AccountId> {
+        fn get_past_votes(
+            &self,
+            account: AccountId,
+            block: BlockNumber,
+        ) -> Result<u128, PSP22Error> {
+            self._ensure_migrated();
+            self.data._check_not_elastic()?;
+            self.votes
+                .get_past_votes(account, block, self.env().block_number())
+        }
+
+        #[ink(message)]
+        fn get_past_total_supply(&self, block: BlockNumber) -> Result<u128, PSP22Error> {
+            self._ensure_migrated();
+            self.data._check_not_elastic()?;
+            self.votes
+                .get_past_total_supply(block, self.env().block_number())
+        }
+
+        #[ink(message)]
+        fn delegate(&mut self, delegatee: AccountId) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.data._check_not_elastic()?;
+            let delegator = self.env().caller();
+            let events = self.votes._delegate(
+                delegator,
+                delegatee,
+                self.data.balance_of(delegator),
+                self.env().block_number(),
+            );
+            self.emit_votes_events(events);
+            Ok(())
+        }
+    }
+
+    impl PSP22Permit for Psp22Standard {
+        #[ink(message)]
+        fn permit(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            value: u128,
+            deadline: u64,
+            signature: [u8; 65],
+        ) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            if self.env().block_timestamp() > deadline {
+                return Err(PSP22Error::PermitExpired);
+            }
+            let nonce = self.permit.nonces(owner);
+            let message_hash = self._permit_message_hash(owner, spender, value, nonce, deadline);
+            let public_key = self
+                .env()
+                .ecdsa_recover(&signature, &message_hash)
+                .map_err(|_| PSP22Error::PermitInvalidSignature)?;
+            let mut signer = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(&public_key, &mut signer);
+            if AccountId::from(signer) != owner {
+                return Err(PSP22Error::PermitInvalidSignature);
+            }
+            if value > 0 {
+                self._check_approval(owner, spender)?;
+            }
+            self.permit._use_nonce(owner);
+            let events = self.data.approve(owner, spender, value)?;
+            self.emit_events(&events);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn nonces(&self, owner: AccountId) -> u64 {
+            self._ensure_migrated();
+            self.permit.nonces(owner)
+        }
+
+        #[ink(message)]
+        fn domain_separator(&self) -> [u8; 32] {
+            self._ensure_migrated();
+            // ink! does not expose a chain id, the genesis hash given at deployment (or set
+            // once after an upgrade) stands in for it since contract addresses can repeat
+            // across chains
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(
+                &(
+                    PERMIT_DOMAIN,
+                    self.permit.genesis_hash(),
+                    self.env().account_id(),
+                ),
+                &mut output,
+            );
+            output
+        }
+
+        #[ink(message)]
+        fn set_genesis_hash(&mut self, genesis_hash: Hash) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            self.permit._set_genesis_hash(genesis_hash)
+        }
+    }
+
+    impl PSP22Metadata for Psp22Standard {
+        #[ink(message)]
+        fn token_name(&self) -> Option<String> {
+            self._ensure_migrated();
+            self.metadata.token_name()
+        }
+        #[ink(message)]
+        fn token_symbol(&self) -> Option<String> {
+            self._ensure_migrated();
+            self.metadata.token_symbol()
+        }
+        #[ink(message)]
+        fn token_decimals(&self) -> u8 {
+            self._ensure_migrated();
+            self.metadata.token_decimals()
+        }
+    }
+
+    impl PSP22MetadataAdmin for Psp22Standard {
+        #[ink(message)]
+        fn set_name(&mut self, name: Option<String>) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(METADATA_ADMIN, Some(Self::env().caller()))?;
+            self.metadata._set_name(name.clone());
+            self.env().emit_event(MetadataUpdated {
+                key: String::from("name"),
+                value: name,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn set_symbol(&mut self, symbol: Option<String>) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(METADATA_ADMIN, Some(Self::env().caller()))?;
+            self.metadata._set_symbol(symbol.clone());
+            self.env().emit_event(MetadataUpdated {
+                key: String::from("symbol"),
+                value: symbol,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn set_attribute(&mut self, key: String, value: Option<String>) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(METADATA_ADMIN, Some(Self::env().caller()))?;
+            self.metadata._set_attribute(&key, &value)?;
+            self.env().emit_event(MetadataUpdated { key, value });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn get_attribute(&self, key: String) -> Option<String> {
+            self._ensure_migrated();
+            self.metadata.get_attribute(key)
+        }
+    }
+
+    impl PSP22Mintable for Psp22Standard {
+        #[ink(message)]
+        fn mint(&mut self, to: AccountId, value: u128) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin._check_role(MINTER, Some(Self::env().caller()))?;
+            self._check_mintable()?;
+            self.cap._check_cap(self.data.total_supply(), value)?;
+            self._update_balances(|data, hooks| data._mint_to(hooks, to, value))?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn batch_mint(&mut self, mints: Vec<(AccountId, u128)>) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin._check_role(MINTER, Some(Self::env().caller()))?;
+            self._check_mintable()?;
+            let total = mints
+                .iter()
+                .fold(0u128, |total, (_, value)| total.saturating_add(*value));
+            self.cap._check_cap(self.data.total_supply(), total)?;
+            self._update_balances(|data, hooks| {
+                let mut events = vec![];
+                for (to, value) in mints {
+                    events.extend(data._mint_to(hooks, to, value)?);
+                }
+                Ok(events)
+            })?;
+            Ok(())
+        }
+    }
+
+    impl PSP22Batch for Psp22Standard {
+        #[ink(message)]
+        fn batch_transfer(&mut self, transfers: Vec<(AccountId, u128)>) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let caller = self.env().caller();
+            for &(to, value) in &transfers {
+                self._do_safe_transfer_check(caller, to, value, vec![])?;
+            }
+            self._update_balances(|data, hooks| {
+                let mut events = vec![];
+                for (to, value) in transfers {
+                    events.extend(data.transfer(hooks, caller, to, value)?);
+                }
+                Ok(events)
+            })?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn batch_transfer_from(
+            &mut self,
+            from: AccountId,
+            transfers: Vec<(AccountId, u128)>,
+        ) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let caller = self.env().caller();
+            for &(to, value) in &transfers {
+                self._do_safe_transfer_check(from, to, value, vec![])?;
+            }
+            self._update_balances(|data, hooks| {
+                let mut events = vec![];
+                for (to, value) in transfers {
+                    events.extend(data.transfer_from(hooks, caller, from, to, value)?);
+                }
+                Ok(events)
+            })?;
+            Ok(())
+        }
+    }
+
+    impl PSP22Burnable for Psp22Standard {
+        #[ink(message)]
+        fn burn(&mut self, value: u128) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let caller = self.env().caller();
+            self._update_balances(|data, hooks| data._burn_from(hooks, caller, value))?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn burn_from(&mut self, from: AccountId, value: u128) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let caller = self.env().caller();
+            self._update_balances(|data, hooks| data.burn_from(hooks, caller, from, value))?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn force_burn(&mut self, from: AccountId, value: u128) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(FORCE_BURNER, Some(Self::env().caller()))?;
+            self.data._unlock_for(from, value);
+            self._force_update_balances(|data, hooks| data._burn_from(hooks, from, value))?;
+            Ok(())
+        }
+    }
+
+    impl Ownable for Psp22Standard {
+        #[ink(message)]
+        fn owner(&self) -> Option<AccountId> {
+            self._ensure_migrated();
             self.ownable.owner()
         }
         #[ink(message)]
         fn renounce_ownership(&mut self) -> Result<(), OwnableError> {
+            self._ensure_migrated();
             self.ownable._check_owner(Some(self.env().caller()))?;
             self.ownable.renounce_ownership()?;
             self.env().emit_event(OwnershipTransferred {
@@ -249,103 +1180,2845 @@ pub mod psp22_standard {
             Ok(())
         }
         #[ink(message)]
+        fn pending_owner(&self) -> Option<AccountId> {
+            self._ensure_migrated();
+            self.ownable.pending_owner()
+        }
+        #[ink(message)]
         fn transfer_ownership(&mut self, new_owner: Option<AccountId>) -> Result<(), OwnableError> {
+            self._ensure_migrated();
             self.ownable._check_owner(Some(self.env().caller()))?;
             self.ownable.transfer_ownership(new_owner)?;
-            self.env().emit_event(OwnershipTransferred {
+            self.env().emit_event(OwnershipTransferStarted {
                 old_owner: self.owner(),
                 new_owner,
             });
 
             Ok(())
         }
+        #[ink(message)]
+        fn accept_ownership(&mut self) -> Result<(), OwnableError> {
+            self._ensure_migrated();
+            let old_owner = self.owner();
+            self.ownable.accept_ownership(self.env().caller())?;
+            self.env().emit_event(OwnershipTransferred {
+                old_owner,
+                new_owner: Some(self.env().caller()),
+            });
+
+            Ok(())
+        }
+        #[ink(message)]
+        fn cancel_ownership_transfer(&mut self) -> Result<(), OwnableError> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            self.ownable.cancel_ownership_transfer()
+        }
     }
 
     impl PSP22Capped for Psp22Standard {
         #[ink(message)]
         fn cap(&self) -> Balance {
+            self._ensure_migrated();
             self.cap.cap()
         }
+
+        #[ink(message)]
+        fn set_cap(&mut self, new_cap: Option<Balance>) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(CAP_MANAGER, Some(Self::env().caller()))?;
+            let old = self.cap._get_cap();
+            self.cap._set_cap(new_cap, self.data.total_supply())?;
+            self.env().emit_event(CapChanged {
+                old,
+                new: self.cap._get_cap(),
+            });
+            Ok(())
+        }
     }
 
-    impl UpgradeableTrait for Psp22Standard {
+    impl Pausable for Psp22Standard {
         #[ink(message)]
-        fn set_code(&mut self, new_code_hash: Hash) -> Result<(), UpgradeableError> {
-            self.ownable._check_owner(Some(self.env().caller()))?;
-            self.env()
-                .set_code_hash(&new_code_hash)
-                .map_err(|_| UpgradeableError::SetCodeHashFailed)
+        fn paused(&self) -> bool {
+            self._ensure_migrated();
+            self.pausable.paused()
+        }
+
+        #[ink(message)]
+        fn pause(&mut self) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin._check_role(PAUSER, Some(Self::env().caller()))?;
+            self.pausable._pause()?;
+            self.env().emit_event(Paused {
+                account: self.env().caller(),
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn unpause(&mut self) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin._check_role(PAUSER, Some(Self::env().caller()))?;
+            self.pausable._unpause()?;
+            self.env().emit_event(Unpaused {
+                account: self.env().caller(),
+            });
+            Ok(())
         }
     }
 
-    impl AccessControl for Psp22Standard {
+    impl PSP22Compliance for Psp22Standard {
         #[ink(message)]
-        fn has_role(&self, role: RoleType, address: Option<AccountId>) -> bool {
-            self.admin._has_role(role, &address)
+        fn is_blocked(&self, account: AccountId) -> bool {
+            self._ensure_migrated();
+            self.compliance.is_blocked(account)
         }
 
         #[ink(message)]
-        fn get_role_admin(&self, role: RoleType) -> RoleType {
+        fn is_allowed(&self, account: AccountId) -> bool {
+            self._ensure_migrated();
+            self.compliance.is_allowed(account)
+        }
+
+        #[ink(message)]
+        fn allowlist_mode(&self) -> bool {
+            self._ensure_migrated();
+            self.compliance.allowlist_mode()
+        }
+
+        #[ink(message)]
+        fn block_account(&mut self, account: AccountId) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
             self.admin
-                ._get_role_admin(role)
-                .unwrap_or(AccessControlData::_default_admin())
+                ._check_role(COMPLIANCE, Some(Self::env().caller()))?;
+            if self.compliance._block(account) {
+                self.env().emit_event(AccountBlocked { account });
+            }
+            Ok(())
         }
+
         #[ink(message)]
-        fn grant_role(
-            &mut self,
-            role: RoleType,
-            account: Option<AccountId>,
-        ) -> Result<(), AccessControlError> {
+        fn unblock_account(&mut self, account: AccountId) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
             self.admin
-                ._check_role(self.get_role_admin(role), Some(Self::env().caller()))?;
-            if self.admin._has_role(role, &account) {
-                return Err(AccessControlError::RoleRedundant);
+                ._check_role(COMPLIANCE, Some(Self::env().caller()))?;
+            if self.compliance._unblock(account) {
+                self.env().emit_event(AccountUnblocked { account });
             }
-            self.admin._add(role, &account);
             Ok(())
         }
+
         #[ink(message)]
-        fn revoke_role(
-            &mut self,
-            role: RoleType,
-            account: Option<AccountId>,
-        ) -> Result<(), AccessControlError> {
+        fn allow_account(&mut self, account: AccountId) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
             self.admin
-                ._check_role(self.get_role_admin(role), Some(Self::env().caller()))?;
-            self.admin._check_role(role, account)?;
-            self.admin._do_revoke_role(role, account);
+                ._check_role(COMPLIANCE, Some(Self::env().caller()))?;
+            if self.compliance._allow(account) {
+                self.env().emit_event(AccountAllowed { account });
+            }
             Ok(())
         }
+
         #[ink(message)]
-        fn renounce_role(
-            &mut self,
-            role: RoleType,
-            account: Option<AccountId>,
-        ) -> Result<(), AccessControlError> {
-            if account != Some(Self::env().caller()) {
-                return Err(AccessControlError::InvalidCaller);
+        fn disallow_account(&mut self, account: AccountId) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(COMPLIANCE, Some(Self::env().caller()))?;
+            if self.compliance._disallow(account) {
+                self.env().emit_event(AccountDisallowed { account });
             }
-            self.admin._check_role(role, account)?;
-            self.admin._do_revoke_role(role, account);
             Ok(())
         }
-    }
 
-    impl AdminTrait for Psp22Standard {
         #[ink(message)]
-        fn withdraw_fee(&mut self, value: Balance, receiver: AccountId) -> Result<(), Error> {
-            self.ownable._check_owner(Some(self.env().caller()))?;
-            if value > Self::env().balance() {
-                return Err(Error::NotEnoughBalance);
+        fn set_allowlist_mode(&mut self, enabled: bool) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(COMPLIANCE, Some(Self::env().caller()))?;
+            if self.compliance.allowlist_mode() != enabled {
+                self.compliance._set_allowlist_mode(enabled);
+                self.env().emit_event(AllowlistModeChanged { enabled });
             }
-            if Self::env().transfer(receiver, value).is_err() {
-                return Err(Error::WithdrawFeeError);
+            Ok(())
+        }
+    }
+
+    impl PSP22WrappedNative for Psp22Standard {
+        #[ink(message, payable)]
+        fn deposit(&mut self) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.wrapped_native._check_enabled()?;
+            let value = self.env().transferred_value();
+            self.cap._check_cap(self.data.total_supply(), value)?;
+            let caller = self.env().caller();
+            self._update_balances(|data, hooks| data._mint_to(hooks, caller, value))?;
+            self.wrapped_native._deposit(value);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn withdraw(&mut self, amount: Balance) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.wrapped_native._check_enabled()?;
+            self.wrapped_native._withdraw(amount)?;
+            let caller = self.env().caller();
+            self._update_balances(|data, hooks| data._burn_from(hooks, caller, amount))?;
+            if self.env().transfer(caller, amount).is_err() {
+                return Err(PSP22Error::Custom(String::from("Native transfer failed")));
             }
             Ok(())
         }
+
         #[ink(message)]
-        fn get_balance(&self) -> Balance {
-            Self::env().balance()
+        fn collateral(&self) -> Balance {
+            self._ensure_migrated();
+            self.wrapped_native.collateral()
+        }
+    }
+
+    impl PSP22Wrapper for Psp22Standard {
+        #[ink(message)]
+        fn deposit_for(&mut self, account: AccountId, amount: u128) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let underlying = self.wrapper._check_underlying()?;
+            self.cap._check_cap(self.data.total_supply(), amount)?;
+            self._underlying_transfer_from(
+                underlying,
+                self.env().caller(),
+                self.env().account_id(),
+                amount,
+            )?;
+            self._update_balances(|data, hooks| data._mint_to(hooks, account, amount))?;
+            self.wrapper._deposit(amount);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn withdraw_to(&mut self, account: AccountId, amount: u128) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let underlying = self.wrapper._check_underlying()?;
+            self.wrapper._withdraw(amount)?;
+            let caller = self.env().caller();
+            self._update_balances(|data, hooks| data._burn_from(hooks, caller, amount))?;
+            self._underlying_transfer(underlying, account, amount)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn recover(&mut self, account: AccountId) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            let underlying = self.wrapper._check_underlying()?;
+            let value = self
+                ._underlying_balance_of(underlying, self.env().account_id())?
+                .saturating_sub(self.wrapper.deposited());
+            let value = match self.data.reward_token() {
+                Some(reward_token) if reward_token == underlying => {
+                    value.saturating_sub(self.data.dividend_reserve())
+                }
+                _ => value,
+            };
+            self.cap._check_cap(self.data.total_supply(), value)?;
+            self._update_balances(|data, hooks| data._mint_to(hooks, account, value))?;
+            self.wrapper._deposit(value);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn underlying(&self) -> Option<AccountId> {
+            self._ensure_migrated();
+            self.wrapper.underlying()
+        }
+    }
+
+    impl PSP22TransferFee for Psp22Standard {
+        #[ink(message)]
+        fn transfer_fee(&self) -> u16 {
+            self._ensure_migrated();
+            self.data.transfer_fee()
+        }
+
+        #[ink(message)]
+        fn transfer_fee_recipient(&self) -> Option<AccountId> {
+            self._ensure_migrated();
+            self.data.transfer_fee_recipient()
+        }
+
+        #[ink(message)]
+        fn is_fee_exempt(&self, account: AccountId) -> bool {
+            self._ensure_migrated();
+            self.data.is_fee_exempt(account)
+        }
+
+        #[ink(message)]
+        fn set_transfer_fee(
+            &mut self,
+            fee: u16,
+            fee_recipient: Option<AccountId>,
+        ) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            self.data._set_transfer_fee(fee, fee_recipient)
+        }
+
+        #[ink(message)]
+        fn set_fee_exempt(&mut self, account: AccountId, exempt: bool) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            self.data._set_fee_exempt(account, exempt);
+            Ok(())
+        }
+    }
+
+    impl PSP22Lockable for Psp22Standard {
+        #[ink(message)]
+        fn transfer_locked(
+            &mut self,
+            to: AccountId,
+            value: u128,
+            unlock_at: u64,
+        ) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let caller = self.env().caller();
+            self._do_safe_transfer_check(caller, to, value, vec![])?;
+            self._update_balances(|data, hooks| {
+                data.transfer_locked(hooks, caller, to, value, unlock_at)
+            })
+        }
+
+        #[ink(message)]
+        fn reject_locked(&mut self, sender: AccountId, unlock_at: u64) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let caller = self.env().caller();
+            let amount = self.data._reject_lock(caller, sender, unlock_at)?;
+            self._update_balances(|data, hooks| {
+                data._update(hooks, Some(caller), Some(sender), amount)
+            })
+        }
+
+        #[ink(message)]
+        fn locks_of(&self, account: AccountId) -> Vec<Lock> {
+            self._ensure_migrated();
+            self.data.locks_of(account)
+        }
+
+        #[ink(message)]
+        fn locked_balance_of(&self, account: AccountId) -> u128 {
+            self._ensure_migrated();
+            self.data.locked_balance_of(account)
+        }
+
+        #[ink(message)]
+        fn unlocked_balance_of(&self, account: AccountId) -> u128 {
+            self._ensure_migrated();
+            self.data.unlocked_balance_of(account)
+        }
+    }
+
+    impl PSP22Dividends for Psp22Standard {
+        #[ink(message, payable)]
+        fn distribute(&mut self) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            if self.data.reward_token().is_some() {
+                return Err(PSP22Error::InvalidRewardAsset);
+            }
+            let value = self.env().transferred_value();
+            self.data._distribute(value)?;
+            self.env().emit_event(DividendsDistributed {
+                from: self.env().caller(),
+                value,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn distribute_token(&mut self, amount: u128) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let reward_token = self
+                .data
+                .reward_token()
+                .ok_or(PSP22Error::InvalidRewardAsset)?;
+            self.data._distribute(amount)?;
+            self._underlying_transfer_from(
+                reward_token,
+                self.env().caller(),
+                self.env().account_id(),
+                amount,
+            )?;
+            self.env().emit_event(DividendsDistributed {
+                from: self.env().caller(),
+                value: amount,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn withdrawable_dividend_of(&self, account: AccountId) -> u128 {
+            self._ensure_migrated();
+            self.data.withdrawable_dividend_of(account)
+        }
+
+        #[ink(message)]
+        fn claim(&mut self) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let caller = self.env().caller();
+            self.compliance._check_account(caller)?;
+            let value = self.data._claim(caller);
+            if value == 0 {
+                return Ok(());
+            }
+            match self.data.reward_token() {
+                Some(reward_token) => self._underlying_transfer(reward_token, caller, value)?,
+                None => {
+                    if self.env().transfer(caller, value).is_err() {
+                        return Err(PSP22Error::Custom(String::from("Native transfer failed")));
+                    }
+                }
+            }
+            self.env().emit_event(DividendClaimed {
+                account: caller,
+                value,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn reward_token(&self) -> Option<AccountId> {
+            self._ensure_migrated();
+            self.data.reward_token()
+        }
+
+        #[ink(message)]
+        fn set_reward_token(&mut self, reward_token: Option<AccountId>) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            self.data._set_reward_token(reward_token)
+        }
+    }
+
+    impl PSP22Rebase for Psp22Standard {
+        #[ink(message)]
+        fn rebase(&mut self, new_total_supply: u128) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(REBASER, Some(Self::env().caller()))?;
+            self.pausable._check_not_paused()?;
+            self.cap._check_cap(new_total_supply, 0)?;
+            let previous_total_supply = self.data.total_supply();
+            self.data._rebase(new_total_supply)?;
+            self.env().emit_event(Rebase {
+                previous_total_supply,
+                new_total_supply,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn shares_of(&self, account: AccountId) -> u128 {
+            self._ensure_migrated();
+            self.data.shares_of(account)
+        }
+
+        #[ink(message)]
+        fn total_shares(&self) -> u128 {
+            self._ensure_migrated();
+            self.data.total_shares()
+        }
+    }
+
+    impl PSP22Vesting for Psp22Standard {
+        #[ink(message)]
+        fn create_vesting(
+            &mut self,
+            beneficiary: AccountId,
+            amount: u128,
+            start: u64,
+            cliff: u64,
+            duration: u64,
+            revocable: bool,
+        ) -> Result<u32, PSP22Error> {
+            self._ensure_migrated();
+            let caller = self.env().caller();
+            self.admin._check_role(VESTING_ADMIN, Some(caller))?;
+            self.data._check_not_elastic()?;
+            let schedule_id = self.vesting._create(VestingSchedule {
+                funder: caller,
+                beneficiary,
+                amount,
+                released: 0,
+                start,
+                cliff,
+                duration,
+                revocable,
+                revoked: false,
+            })?;
+            let this = self.env().account_id();
+            self._update_balances(|data, hooks| {
+                data._update(hooks, Some(caller), Some(this), amount)
+            })?;
+            self.env().emit_event(VestingCreated {
+                schedule_id,
+                beneficiary,
+                amount,
+            });
+            Ok(schedule_id)
+        }
+
+        #[ink(message)]
+        fn release(&mut self, schedule_id: u32) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let (beneficiary, amount) = self
+                .vesting
+                ._release(schedule_id, self.env().block_timestamp())?;
+            if amount == 0 {
+                return Ok(());
+            }
+            let this = self.env().account_id();
+            self._update_balances(|data, hooks| {
+                data._update(hooks, Some(this), Some(beneficiary), amount)
+            })?;
+            self.env().emit_event(VestingReleased {
+                schedule_id,
+                beneficiary,
+                amount,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn releasable(&self, schedule_id: u32) -> Result<u128, PSP22Error> {
+            self._ensure_migrated();
+            self.vesting
+                .releasable(schedule_id, self.env().block_timestamp())
+        }
+
+        #[ink(message)]
+        fn revoke(&mut self, schedule_id: u32) -> Result<(), PSP22Error> {
+            self._ensure_migrated();
+            let caller = self.env().caller();
+            self.admin._check_role(VESTING_ADMIN, Some(caller))?;
+            let (funder, unvested) = self
+                .vesting
+                ._revoke(schedule_id, self.env().block_timestamp())?;
+            if unvested > 0 {
+                let this = self.env().account_id();
+                self._update_balances(|data, hooks| {
+                    data._update(hooks, Some(this), Some(funder), unvested)
+                })?;
+            }
+            self.env().emit_event(VestingRevoked {
+                schedule_id,
+                unvested,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn vesting_schedule(&self, schedule_id: u32) -> Option<VestingSchedule> {
+            self._ensure_migrated();
+            self.vesting.schedule(schedule_id)
+        }
+    }
+
+    impl PSP22FlashLender for Psp22Standard {
+        #[ink(message)]
+        fn max_flashloan(&self, token: AccountId) -> u128 {
+            self._ensure_migrated();
+            if token != self.env().account_id() || self.data.elastic() {
+                return 0;
+            }
+            self.cap.cap().saturating_sub(self.data.total_supply())
+        }
+
+        #[ink(message)]
+        fn flash_fee(&self, token: AccountId, amount: u128) -> Result<u128, FlashLenderError> {
+            self._ensure_migrated();
+            if token != self.env().account_id() {
+                return Err(FlashLenderError::WrongTokenAddress);
+            }
+            Ok(self.flash_lender.flash_fee(amount))
+        }
+
+        #[ink(message)]
+        fn flashloan(
+            &mut self,
+            receiver: AccountId,
+            token: AccountId,
+            amount: u128,
+            data: Vec<u8>,
+        ) -> Result<(), FlashLenderError> {
+            self._ensure_migrated();
+            // Minted and repaid shares round differently, the repayment would fall short
+            self.data._check_not_elastic()?;
+            let fee = self.flash_fee(token, amount)?;
+            self.cap._check_cap(self.data.total_supply(), amount)?;
+            self._update_balances(|data, hooks| data._mint_to(hooks, receiver, amount))?;
+
+            self._on_flashloan(receiver, amount, fee, data)?;
+
+            let repayment = amount.saturating_add(fee);
+            let this = self.env().account_id();
+            if self.data.allowance(receiver, this) < repayment {
+                return Err(FlashLenderError::AllowanceDoesNotAllowRefund);
+            }
+            let fee_recipient = self.flash_lender.fee_recipient();
+            self._update_balances(|data, hooks| {
+                let mut events = data.decrease_allowance(receiver, this, repayment)?;
+                match fee_recipient {
+                    Some(fee_recipient) if fee > 0 => {
+                        events.extend(data._burn_from(hooks, receiver, amount)?);
+                        events.extend(data._update(
+                            hooks,
+                            Some(receiver),
+                            Some(fee_recipient),
+                            fee,
+                        )?);
+                    }
+                    _ => events.extend(data._burn_from(hooks, receiver, repayment)?),
+                }
+                Ok(events)
+            })?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn set_flash_fee(
+            &mut self,
+            fee: u16,
+            fee_recipient: Option<AccountId>,
+        ) -> Result<(), FlashLenderError> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            self.flash_lender._set_fee(fee, fee_recipient)
+        }
+    }
+
+    impl UpgradeableTrait for Psp22Standard {
+        #[ink(message)]
+        fn schedule_upgrade(
+            &mut self,
+            new_code_hash: Hash,
+            eta: u64,
+        ) -> Result<(), UpgradeableError> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            self.upgradeable
+                ._schedule(new_code_hash, eta, self.env().block_timestamp())?;
+            self.env().emit_event(UpgradeScheduled {
+                code_hash: new_code_hash,
+                eta,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn execute_upgrade(&mut self) -> Result<(), UpgradeableError> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            let code_hash = self.upgradeable._execute(self.env().block_timestamp())?;
+            self.env()
+                .set_code_hash(&code_hash)
+                .map_err(|_| UpgradeableError::SetCodeHashFailed)?;
+            self.env().emit_event(UpgradeExecuted { code_hash });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn cancel_upgrade(&mut self) -> Result<(), UpgradeableError> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            let code_hash = self.upgradeable._cancel()?;
+            self.env().emit_event(UpgradeCancelled { code_hash });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn pending_upgrade(&self) -> Option<(Hash, u64)> {
+            self._ensure_migrated();
+            self.upgradeable.pending_upgrade()
+        }
+
+        #[ink(message)]
+        fn upgrade_delay(&self) -> u64 {
+            self._ensure_migrated();
+            self.upgradeable.delay()
+        }
+
+        #[ink(message)]
+        fn set_upgrade_delay(&mut self, delay: u64) -> Result<(), UpgradeableError> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            self.upgradeable._set_delay(delay)
+        }
+
+        #[ink(message)]
+        fn storage_version(&self) -> u32 {
+            self.migration.version()
+        }
+
+        #[ink(message)]
+        fn migrate(&mut self) -> Result<(), UpgradeableError> {
+            if self.migration._is_migrated() {
+                return Err(UpgradeableError::AlreadyMigrated);
+            }
+            let from_version = self.migration.version();
+            for version in from_version + 1..=STORAGE_VERSION {
+                self._migration_step(version);
+                self.migration._set_version(version);
+            }
+            self.env().emit_event(Migrated {
+                from_version,
+                to_version: STORAGE_VERSION,
+            });
+            Ok(())
+        }
+    }
+
+    impl AccessControl for Psp22Standard {
+        #[ink(message)]
+        fn has_role(&self, role: RoleType, address: Option<AccountId>) -> bool {
+            self._ensure_migrated();
+            self.admin._has_role(role, &address)
+        }
+
+        #[ink(message)]
+        fn get_role_admin(&self, role: RoleType) -> RoleType {
+            self._ensure_migrated();
+            self.admin
+                ._get_role_admin(role)
+                .unwrap_or(AccessControlData::_default_admin())
+        }
+        #[ink(message)]
+        fn grant_role(
+            &mut self,
+            role: RoleType,
+            account: Option<AccountId>,
+        ) -> Result<(), AccessControlError> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(self.get_role_admin(role), Some(Self::env().caller()))?;
+            if self.admin._has_role(role, &account) {
+                return Err(AccessControlError::RoleRedundant);
+            }
+            self.admin._add(role, &account);
+            self.env().emit_event(RoleGranted {
+                role,
+                grantee: account,
+                grantor: Some(self.env().caller()),
+            });
+            Ok(())
+        }
+        #[ink(message)]
+        fn revoke_role(
+            &mut self,
+            role: RoleType,
+            account: Option<AccountId>,
+        ) -> Result<(), AccessControlError> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(self.get_role_admin(role), Some(Self::env().caller()))?;
+            self.admin._check_role(role, account)?;
+            self.admin._do_revoke_role(role, account);
+            self.env().emit_event(RoleRevoked {
+                role,
+                account,
+                sender: self.env().caller(),
+            });
+            Ok(())
+        }
+        #[ink(message)]
+        fn renounce_role(
+            &mut self,
+            role: RoleType,
+            account: Option<AccountId>,
+        ) -> Result<(), AccessControlError> {
+            self._ensure_migrated();
+            if account != Some(Self::env().caller()) {
+                return Err(AccessControlError::InvalidCaller);
+            }
+            self.admin._check_role(role, account)?;
+            self.admin._do_revoke_role(role, account);
+            self.env().emit_event(RoleRevoked {
+                role,
+                account,
+                sender: self.env().caller(),
+            });
+            Ok(())
+        }
+        #[ink(message)]
+        fn set_role_admin(
+            &mut self,
+            role: RoleType,
+            new_admin: RoleType,
+        ) -> Result<(), AccessControlError> {
+            self._ensure_migrated();
+            let previous_admin = self.get_role_admin(role);
+            self.admin
+                ._check_role(previous_admin, Some(Self::env().caller()))?;
+            self.admin._set_role_admin(role, new_admin);
+            self.env().emit_event(RoleAdminChanged {
+                role,
+                previous_admin,
+                new_admin,
+            });
+            Ok(())
+        }
+    }
+
+    impl AccessControlEnumerable for Psp22Standard {
+        #[ink(message)]
+        fn get_role_member(&self, role: RoleType, index: u32) -> Option<AccountId> {
+            self._ensure_migrated();
+            self.admin._get_role_member(role, index)
+        }
+
+        #[ink(message)]
+        fn get_role_member_count(&self, role: RoleType) -> u32 {
+            self._ensure_migrated();
+            self.admin._get_role_member_count(role)
+        }
+
+        #[ink(message)]
+        fn index_role_members(
+            &mut self,
+            role: RoleType,
+            members: Vec<Option<AccountId>>,
+        ) -> Result<(), AccessControlError> {
+            self._ensure_migrated();
+            self.admin
+                ._check_role(self.get_role_admin(role), Some(Self::env().caller()))?;
+            for member in members {
+                self.admin._index(role, &member)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl AdminTrait for Psp22Standard {
+        #[ink(message)]
+        fn withdraw_fee(&mut self, value: Balance, receiver: AccountId) -> Result<(), Error> {
+            self._ensure_migrated();
+            self.ownable._check_owner(Some(self.env().caller()))?;
+            // Native collateral of wrapped tokens and unclaimed dividends are not fees
+            let mut reserved = self.wrapped_native.collateral();
+            if self.data.reward_token().is_none() {
+                reserved = reserved.saturating_add(self.data.dividend_reserve());
+            }
+            let fees = Self::env().balance().saturating_sub(reserved);
+            if value > fees {
+                return Err(Error::NotEnoughBalance);
+            }
+            if Self::env().transfer(receiver, value).is_err() {
+                return Err(Error::WithdrawFeeError);
+            }
+            Ok(())
+        }
+        #[ink(message)]
+        fn get_balance(&self) -> Balance {
+            self._ensure_migrated();
+            Self::env().balance()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::{MAX_LOCKS, MAX_TRANSFER_FEE};
+        use ink::env::test::{default_accounts, recorded_events, set_caller, DefaultAccounts};
+        use ink::env::DefaultEnvironment;
+
+        type Event = <Psp22Standard as ::ink::reflect::ContractEventBase>::Type;
+
+        const CAP: Balance = 1_000_000;
+
+        fn genesis_hash() -> Hash {
+            Hash::from([0x11; 32])
+        }
+
+        fn accounts() -> DefaultAccounts<DefaultEnvironment> {
+            default_accounts::<DefaultEnvironment>()
+        }
+
+        fn set_sender(sender: AccountId) {
+            set_caller::<DefaultEnvironment>(sender);
+        }
+
+        fn decode_events() -> Vec<Event> {
+            recorded_events()
+                .map(|event| {
+                    <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer")
+                })
+                .collect()
+        }
+
+        fn assert_transfer_event(
+            event: &Event,
+            expected_from: Option<AccountId>,
+            expected_to: Option<AccountId>,
+            expected_value: u128,
+        ) {
+            if let Event::Transfer(Transfer { from, to, value }) = event {
+                assert_eq!(*from, expected_from, "encountered invalid Transfer.from");
+                assert_eq!(*to, expected_to, "encountered invalid Transfer.to");
+                assert_eq!(*value, expected_value, "encountered invalid Transfer.value");
+            } else {
+                panic!("encountered unexpected event kind: expected a Transfer event")
+            }
+        }
+
+        fn assert_approval_event(
+            event: &Event,
+            expected_owner: AccountId,
+            expected_spender: AccountId,
+            expected_amount: u128,
+        ) {
+            if let Event::Approval(Approval {
+                owner,
+                spender,
+                amount,
+            }) = event
+            {
+                assert_eq!(*owner, expected_owner, "encountered invalid Approval.owner");
+                assert_eq!(
+                    *spender, expected_spender,
+                    "encountered invalid Approval.spender"
+                );
+                assert_eq!(
+                    *amount, expected_amount,
+                    "encountered invalid Approval.amount"
+                );
+            } else {
+                panic!("encountered unexpected event kind: expected an Approval event")
+            }
+        }
+
+        /// Deploys the token as alice, grants her `MINTER` and mints `supply` to her.
+        /// Emits two `RoleGranted` events before the optional mint.
+        fn setup(supply: u128) -> Psp22Standard {
+            let accounts = accounts();
+            // The off-chain contract account defaults to alice, move it out of the way
+            ink::env::test::set_callee::<DefaultEnvironment>(accounts.frank);
+            set_sender(accounts.alice);
+            let mut token = Psp22Standard::new(
+                CAP,
+                Some(String::from("Token")),
+                Some(String::from("TKN")),
+                18,
+                genesis_hash(),
+            );
+            token.grant_role(MINTER, Some(accounts.alice)).unwrap();
+            if supply > 0 {
+                token.mint(accounts.alice, supply).unwrap();
+            }
+            token
+        }
+
+        /// Deploys a wrapped native token as alice and grants her `MINTER`.
+        fn setup_wrapped_native(cap: Balance) -> Psp22Standard {
+            let accounts = accounts();
+            ink::env::test::set_callee::<DefaultEnvironment>(accounts.frank);
+            set_sender(accounts.alice);
+            let mut token = Psp22Standard::new_wrapped_native(cap, None, None, 18, genesis_hash());
+            token.grant_role(MINTER, Some(accounts.alice)).unwrap();
+            token
+        }
+
+        #[ink::test]
+        fn new_works() {
+            let accounts = accounts();
+            set_sender(accounts.alice);
+            let token = Psp22Standard::new(
+                CAP,
+                Some(String::from("Token")),
+                Some(String::from("TKN")),
+                18,
+                genesis_hash(),
+            );
+            assert_eq!(token.token_name(), Some(String::from("Token")));
+            assert_eq!(token.token_symbol(), Some(String::from("TKN")));
+            assert_eq!(token.token_decimals(), 18);
+            assert_eq!(token.cap(), CAP);
+            assert_eq!(token.total_supply(), 0);
+            assert_eq!(token.owner(), Some(accounts.alice));
+            assert!(token.has_role(AccessControlData::_default_admin(), Some(accounts.alice)));
+            assert!(!token.has_role(MINTER, Some(accounts.alice)));
+            assert!(!token.paused());
+        }
+
+        #[ink::test]
+        #[should_panic]
+        fn new_fails_with_zero_cap() {
+            Psp22Standard::new(0, None, None, 0, genesis_hash());
+        }
+
+        #[ink::test]
+        fn transfer_works() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.transfer(accounts.bob, 10, Vec::new()), Ok(()));
+            assert_eq!(token.balance_of(accounts.alice), 90);
+            assert_eq!(token.balance_of(accounts.bob), 10);
+            assert_eq!(token.total_supply(), 100);
+
+            let events = decode_events();
+            assert_eq!(events.len(), 4);
+            assert_transfer_event(&events[2], None, Some(accounts.alice), 100);
+            assert_transfer_event(&events[3], Some(accounts.alice), Some(accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn transfer_fails_with_insufficient_balance() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(
+                token.transfer(accounts.bob, 101, Vec::new()),
+                Err(PSP22Error::InsufficientBalance)
+            );
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(token.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn transfer_to_self_or_zero_value_is_noop() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.transfer(accounts.alice, 10, Vec::new()), Ok(()));
+            assert_eq!(token.transfer(accounts.bob, 0, Vec::new()), Ok(()));
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(decode_events().len(), 3);
+        }
+
+        #[ink::test]
+        fn transfer_from_works() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.approve(accounts.bob, 30), Ok(()));
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.charlie, 20, Vec::new()),
+                Ok(())
+            );
+            assert_eq!(token.balance_of(accounts.alice), 80);
+            assert_eq!(token.balance_of(accounts.charlie), 20);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 10);
+
+            let events = decode_events();
+            assert_eq!(events.len(), 6);
+            assert_approval_event(&events[3], accounts.alice, accounts.bob, 30);
+            assert_approval_event(&events[4], accounts.alice, accounts.bob, 10);
+            assert_transfer_event(&events[5], Some(accounts.alice), Some(accounts.charlie), 20);
+        }
+
+        #[ink::test]
+        fn transfer_from_fails_with_insufficient_allowance() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.approve(accounts.bob, 10), Ok(()));
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.charlie, 20, Vec::new()),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+            assert_eq!(token.balance_of(accounts.alice), 100);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn transfer_from_fails_with_insufficient_balance() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.approve(accounts.bob, 200), Ok(()));
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.charlie, 150, Vec::new()),
+                Err(PSP22Error::InsufficientBalance)
+            );
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 200);
+        }
+
+        #[ink::test]
+        fn approve_and_allowance_changes_work() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(token.increase_allowance(accounts.bob, 5), Ok(()));
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 15);
+            assert_eq!(token.decrease_allowance(accounts.bob, 15), Ok(()));
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 0);
+            assert_eq!(
+                token.decrease_allowance(accounts.bob, 1),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+
+            let events = decode_events();
+            assert_eq!(events.len(), 6);
+            assert_approval_event(&events[3], accounts.alice, accounts.bob, 10);
+            assert_approval_event(&events[4], accounts.alice, accounts.bob, 15);
+            assert_approval_event(&events[5], accounts.alice, accounts.bob, 0);
+        }
+
+        fn sign_permit(
+            token: &Psp22Standard,
+            secret_key: &secp256k1::SecretKey,
+            spender: AccountId,
+            value: u128,
+            nonce: u64,
+            deadline: u64,
+        ) -> (AccountId, [u8; 65]) {
+            let public_key =
+                secp256k1::PublicKey::from_secret_key(secp256k1::SECP256K1, secret_key);
+            let mut owner = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(&public_key.serialize(), &mut owner);
+            let owner = AccountId::from(owner);
+
+            let message_hash = token._permit_message_hash(owner, spender, value, nonce, deadline);
+            let message = secp256k1::Message::from_slice(&message_hash).unwrap();
+            let (recovery_id, compact) = secp256k1::SECP256K1
+                .sign_ecdsa_recoverable(&message, secret_key)
+                .serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = recovery_id.to_i32() as u8;
+            (owner, signature)
+        }
+
+        #[ink::test]
+        fn permit_works() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            let secret_key = secp256k1::SecretKey::from_slice(&[7u8; 32]).unwrap();
+            let (owner, signature) = sign_permit(&token, &secret_key, accounts.bob, 50, 0, 1_000);
+
+            set_sender(accounts.charlie);
+            assert_eq!(token.nonces(owner), 0);
+            assert_eq!(
+                token.permit(owner, accounts.bob, 50, 1_000, signature),
+                Ok(())
+            );
+            assert_eq!(token.allowance(owner, accounts.bob), 50);
+            assert_eq!(token.nonces(owner), 1);
+
+            // The nonce was consumed, so the signature cannot be replayed
+            assert_eq!(
+                token.permit(owner, accounts.bob, 50, 1_000, signature),
+                Err(PSP22Error::PermitInvalidSignature)
+            );
+
+            let events = decode_events();
+            assert_eq!(events.len(), 3);
+            assert_approval_event(&events[2], owner, accounts.bob, 50);
+        }
+
+        #[ink::test]
+        fn permit_domain_separates_chains() {
+            let accounts = accounts();
+            let token = setup(0);
+            let separator = token.domain_separator();
+
+            // Same deployer and address, different chain
+            set_sender(accounts.alice);
+            let other = Psp22Standard::new(CAP, None, None, 18, Hash::from([0x22; 32]));
+            assert_ne!(other.domain_separator(), separator);
+        }
+
+        #[ink::test]
+        fn owner_sets_genesis_hash_of_upgraded_tokens_once() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            let separator = token.domain_separator();
+            assert_eq!(
+                token.set_genesis_hash(Hash::from([0x22; 32])),
+                Err(PSP22Error::GenesisHashAlreadySet)
+            );
+
+            // Tokens deployed before the genesis hash was part of the domain
+            token.permit._init_genesis_hash(Hash::default());
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.set_genesis_hash(genesis_hash()),
+                Err(PSP22Error::OwnableError(OwnableError::CallerIsNotOwner))
+            );
+            set_sender(accounts.alice);
+            assert_eq!(token.set_genesis_hash(genesis_hash()), Ok(()));
+            assert_eq!(token.domain_separator(), separator);
+            assert_eq!(
+                token.set_genesis_hash(Hash::from([0x22; 32])),
+                Err(PSP22Error::GenesisHashAlreadySet)
+            );
+        }
+
+        #[ink::test]
+        fn permit_fails_with_wrong_signer_or_expired_deadline() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            let secret_key = secp256k1::SecretKey::from_slice(&[7u8; 32]).unwrap();
+            let (owner, signature) = sign_permit(&token, &secret_key, accounts.bob, 50, 0, 1_000);
+
+            assert_eq!(
+                token.permit(owner, accounts.bob, 60, 1_000, signature),
+                Err(PSP22Error::PermitInvalidSignature)
+            );
+            assert_eq!(
+                token.permit(accounts.alice, accounts.bob, 50, 1_000, signature),
+                Err(PSP22Error::PermitInvalidSignature)
+            );
+            ink::env::test::set_block_timestamp::<DefaultEnvironment>(1_001);
+            assert_eq!(
+                token.permit(owner, accounts.bob, 50, 1_000, signature),
+                Err(PSP22Error::PermitExpired)
+            );
+            assert_eq!(token.allowance(owner, accounts.bob), 0);
+            assert_eq!(token.nonces(owner), 0);
+        }
+
+        #[ink::test]
+        fn snapshot_works() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(
+                token.snapshot(),
+                Err(PSP22Error::AccessControlError(
+                    AccessControlError::MissingRole
+                ))
+            );
+            assert_eq!(token.grant_role(SNAPSHOT, Some(accounts.alice)), Ok(()));
+            assert_eq!(token.snapshot(), Ok(1));
+            assert_eq!(token.transfer(accounts.bob, 40, Vec::new()), Ok(()));
+            assert_eq!(token.mint(accounts.bob, 10), Ok(()));
+            assert_eq!(token.snapshot(), Ok(2));
+
+            assert_eq!(token.balance_of_at(accounts.alice, 1), Ok(100));
+            assert_eq!(token.balance_of_at(accounts.bob, 1), Ok(0));
+            assert_eq!(token.total_supply_at(1), Ok(100));
+            assert_eq!(token.balance_of_at(accounts.bob, 2), Ok(50));
+            assert_eq!(token.total_supply_at(2), Ok(110));
+            assert_eq!(token.total_supply_at(3), Err(PSP22Error::SnapshotNotFound));
+
+            let events = decode_events();
+            assert!(matches!(events[4], Event::Snapshot(Snapshot { id: 1 })));
+            assert!(matches!(events[7], Event::Snapshot(Snapshot { id: 2 })));
+        }
+
+        #[ink::test]
+        fn votes_follow_delegation_and_balances() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.get_votes(accounts.alice), 0);
+            assert_eq!(token.delegate(accounts.alice), Ok(()));
+            assert_eq!(token.delegates(accounts.alice), Some(accounts.alice));
+            assert_eq!(token.get_votes(accounts.alice), 100);
+
+            let events = decode_events();
+            assert!(matches!(
+                events[3],
+                Event::DelegateChanged(DelegateChanged { delegator, from_delegate: None, to_delegate })
+                    if delegator == accounts.alice && to_delegate == Some(accounts.alice)
+            ));
+            assert!(matches!(
+                events[4],
+                Event::DelegateVotesChanged(DelegateVotesChanged { delegate, previous_votes: 0, new_votes: 100 })
+                    if delegate == accounts.alice
+            ));
+
+            ink::env::test::advance_block::<DefaultEnvironment>();
+            set_sender(accounts.bob);
+            assert_eq!(token.delegate(accounts.charlie), Ok(()));
+            set_sender(accounts.alice);
+            assert_eq!(token.transfer(accounts.bob, 30, Vec::new()), Ok(()));
+            assert_eq!(token.get_votes(accounts.alice), 70);
+            assert_eq!(token.get_votes(accounts.charlie), 30);
+
+            ink::env::test::advance_block::<DefaultEnvironment>();
+            set_sender(accounts.bob);
+            assert_eq!(token.burn(10), Ok(()));
+            set_sender(accounts.alice);
+            assert_eq!(token.mint(accounts.alice, 5), Ok(()));
+            assert_eq!(token.get_votes(accounts.alice), 75);
+            assert_eq!(token.get_votes(accounts.charlie), 20);
+
+            ink::env::test::advance_block::<DefaultEnvironment>();
+            assert_eq!(token.get_past_votes(accounts.alice, 0), Ok(100));
+            assert_eq!(token.get_past_votes(accounts.charlie, 0), Ok(0));
+            assert_eq!(token.get_past_votes(accounts.alice, 1), Ok(70));
+            assert_eq!(token.get_past_votes(accounts.charlie, 1), Ok(30));
+            assert_eq!(token.get_past_votes(accounts.alice, 2), Ok(75));
+            assert_eq!(token.get_past_total_supply(0), Ok(100));
+            assert_eq!(token.get_past_total_supply(2), Ok(95));
+            assert_eq!(
+                token.get_past_votes(accounts.alice, 3),
+                Err(PSP22Error::FutureLookup)
+            );
+        }
+
+        #[ink::test]
+        fn metadata_admin_works() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            assert_eq!(
+                token.set_name(Some(String::from("New"))),
+                Err(PSP22Error::AccessControlError(
+                    AccessControlError::MissingRole
+                ))
+            );
+            assert_eq!(
+                token.grant_role(METADATA_ADMIN, Some(accounts.alice)),
+                Ok(())
+            );
+            assert_eq!(token.set_name(Some(String::from("New"))), Ok(()));
+            assert_eq!(token.set_symbol(None), Ok(()));
+            let logo = String::from("logo");
+            assert_eq!(
+                token.set_attribute(logo.clone(), Some(String::from("ipfs://logo"))),
+                Ok(())
+            );
+            assert_eq!(
+                token.set_attribute(String::from("decimals"), Some(String::from("6"))),
+                Err(PSP22Error::Custom(String::from("Reserved metadata key")))
+            );
+
+            assert_eq!(token.token_name(), Some(String::from("New")));
+            assert_eq!(token.token_symbol(), None);
+            assert_eq!(token.token_decimals(), 18);
+            assert_eq!(
+                token.get_attribute(logo.clone()),
+                Some(String::from("ipfs://logo"))
+            );
+            assert_eq!(token.set_attribute(logo.clone(), None), Ok(()));
+            assert_eq!(token.get_attribute(logo), None);
+
+            let events = decode_events();
+            assert_eq!(events.len(), 7);
+            assert!(matches!(
+                &events[3],
+                Event::MetadataUpdated(MetadataUpdated { key, value })
+                    if key == "name" && value.as_deref() == Some("New")
+            ));
+            assert!(matches!(
+                &events[4],
+                Event::MetadataUpdated(MetadataUpdated { key, value: None }) if key == "symbol"
+            ));
+            assert!(matches!(
+                &events[6],
+                Event::MetadataUpdated(MetadataUpdated { key, value: None }) if key == "logo"
+            ));
+        }
+
+        #[ink::test]
+        fn mint_requires_minter_role() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.mint(accounts.bob, 10),
+                Err(PSP22Error::AccessControlError(
+                    AccessControlError::MissingRole
+                ))
+            );
+            assert_eq!(token.total_supply(), 0);
+        }
+
+        #[ink::test]
+        fn mint_respects_cap() {
+            let accounts = accounts();
+            let mut token = setup(CAP - 10);
+            assert_eq!(token.mint(accounts.bob, 11), Err(PSP22Error::CapExceeded));
+            assert_eq!(token.mint(accounts.bob, 10), Ok(()));
+            assert_eq!(token.total_supply(), CAP);
+            assert_eq!(token.balance_of(accounts.bob), 10);
+
+            let events = decode_events();
+            assert_eq!(events.len(), 4);
+            assert_transfer_event(&events[3], None, Some(accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn batch_mint_checks_aggregate_cap() {
+            let accounts = accounts();
+            let mut token = setup(CAP - 10);
+            assert_eq!(
+                token.batch_mint(vec![(accounts.bob, 6), (accounts.charlie, 5)]),
+                Err(PSP22Error::CapExceeded)
+            );
+            assert_eq!(
+                token.batch_mint(vec![(accounts.bob, 6), (accounts.charlie, 4)]),
+                Ok(())
+            );
+            assert_eq!(token.balance_of(accounts.bob), 6);
+            assert_eq!(token.balance_of(accounts.charlie), 4);
+            assert_eq!(token.total_supply(), CAP);
+
+            let events = decode_events();
+            assert_eq!(events.len(), 5);
+            assert_transfer_event(&events[3], None, Some(accounts.bob), 6);
+            assert_transfer_event(&events[4], None, Some(accounts.charlie), 4);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.batch_mint(vec![(accounts.bob, 1)]),
+                Err(PSP22Error::AccessControlError(
+                    AccessControlError::MissingRole
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn batch_transfer_works() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(
+                token.batch_transfer(vec![(accounts.bob, 10), (accounts.charlie, 20)]),
+                Ok(())
+            );
+            assert_eq!(token.balance_of(accounts.alice), 70);
+            assert_eq!(token.balance_of(accounts.bob), 10);
+            assert_eq!(token.balance_of(accounts.charlie), 20);
+            assert_eq!(
+                token.batch_transfer(vec![(accounts.bob, 10), (accounts.charlie, 100)]),
+                Err(PSP22Error::InsufficientBalance)
+            );
+
+            let events = decode_events();
+            assert_eq!(events.len(), 5);
+            assert_transfer_event(&events[3], Some(accounts.alice), Some(accounts.bob), 10);
+            assert_transfer_event(&events[4], Some(accounts.alice), Some(accounts.charlie), 20);
+        }
+
+        #[ink::test]
+        fn batch_transfer_from_spends_allowance_per_leg() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.approve(accounts.bob, 25), Ok(()));
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.batch_transfer_from(
+                    accounts.alice,
+                    vec![(accounts.charlie, 10), (accounts.django, 10)]
+                ),
+                Ok(())
+            );
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 5);
+            assert_eq!(token.balance_of(accounts.charlie), 10);
+            assert_eq!(token.balance_of(accounts.django), 10);
+            assert_eq!(
+                token.batch_transfer_from(accounts.alice, vec![(accounts.charlie, 10)]),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+        }
+
+        #[ink::test]
+        fn blocklist_restricts_transfers() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(
+                token.block_account(accounts.bob),
+                Err(PSP22Error::AccessControlError(
+                    AccessControlError::MissingRole
+                ))
+            );
+            assert_eq!(token.grant_role(COMPLIANCE, Some(accounts.alice)), Ok(()));
+            assert_eq!(token.transfer(accounts.bob, 10, vec![]), Ok(()));
+            assert_eq!(token.block_account(accounts.bob), Ok(()));
+            assert!(token.is_blocked(accounts.bob));
+
+            assert_eq!(
+                token.transfer(accounts.bob, 10, vec![]),
+                Err(PSP22Error::AccountRestricted)
+            );
+            assert_eq!(
+                token.mint(accounts.bob, 10),
+                Err(PSP22Error::AccountRestricted)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.transfer(accounts.charlie, 10, vec![]),
+                Err(PSP22Error::AccountRestricted)
+            );
+            assert_eq!(token.burn(10), Err(PSP22Error::AccountRestricted));
+
+            set_sender(accounts.alice);
+            assert_eq!(token.unblock_account(accounts.bob), Ok(()));
+            assert_eq!(token.transfer(accounts.bob, 10, vec![]), Ok(()));
+
+            let events = decode_events();
+            assert!(matches!(
+                events[5],
+                Event::AccountBlocked(AccountBlocked { account }) if account == accounts.bob
+            ));
+            assert!(matches!(
+                events[6],
+                Event::AccountUnblocked(AccountUnblocked { account }) if account == accounts.bob
+            ));
+        }
+
+        #[ink::test]
+        fn blocklist_restricts_operators_and_approvals() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.grant_role(COMPLIANCE, Some(accounts.alice)), Ok(()));
+            assert_eq!(token.approve(accounts.bob, 50), Ok(()));
+            assert_eq!(token.block_account(accounts.bob), Ok(()));
+
+            // Bob moves nothing of his own, yet cannot spend his allowance
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.charlie, 10, vec![]),
+                Err(PSP22Error::AccountRestricted)
+            );
+            assert_eq!(
+                token.batch_transfer_from(accounts.alice, vec![(accounts.charlie, 10)]),
+                Err(PSP22Error::AccountRestricted)
+            );
+            assert_eq!(
+                token.burn_from(accounts.alice, 10),
+                Err(PSP22Error::AccountRestricted)
+            );
+            assert_eq!(
+                token.approve(accounts.charlie, 10),
+                Err(PSP22Error::AccountRestricted)
+            );
+            assert_eq!(token.balance_of(accounts.alice), 100);
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                token.increase_allowance(accounts.bob, 10),
+                Err(PSP22Error::AccountRestricted)
+            );
+            // Allowances of a restricted spender can still be revoked
+            assert_eq!(token.approve(accounts.bob, 0), Ok(()));
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn allowlist_mode_restricts_transfers() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.grant_role(COMPLIANCE, Some(accounts.alice)), Ok(()));
+            assert_eq!(token.set_allowlist_mode(true), Ok(()));
+            assert!(token.allowlist_mode());
+            assert_eq!(
+                token.transfer(accounts.bob, 10, vec![]),
+                Err(PSP22Error::AccountRestricted)
+            );
+            assert_eq!(token.allow_account(accounts.alice), Ok(()));
+            assert_eq!(token.allow_account(accounts.bob), Ok(()));
+            assert_eq!(token.transfer(accounts.bob, 10, vec![]), Ok(()));
+            assert_eq!(token.disallow_account(accounts.bob), Ok(()));
+            assert_eq!(
+                token.transfer(accounts.bob, 10, vec![]),
+                Err(PSP22Error::AccountRestricted)
+            );
+            assert_eq!(token.set_allowlist_mode(false), Ok(()));
+            assert_eq!(token.transfer(accounts.bob, 10, vec![]), Ok(()));
+        }
+
+        #[ink::test]
+        fn transfer_fee_goes_to_recipient() {
+            let accounts = accounts();
+            let mut token = setup(10_000);
+            assert_eq!(
+                token.set_transfer_fee(MAX_TRANSFER_FEE + 1, None),
+                Err(PSP22Error::InvalidTransferFee)
+            );
+            assert_eq!(token.set_transfer_fee(100, Some(accounts.eve)), Ok(()));
+            assert_eq!(token.transfer(accounts.bob, 1_000, vec![]), Ok(()));
+            assert_eq!(token.balance_of(accounts.alice), 9_000);
+            assert_eq!(token.balance_of(accounts.bob), 990);
+            assert_eq!(token.balance_of(accounts.eve), 10);
+            assert_eq!(token.total_supply(), 10_000);
+
+            let events = decode_events();
+            assert_eq!(events.len(), 5);
+            assert_transfer_event(&events[3], Some(accounts.alice), Some(accounts.bob), 990);
+            assert_transfer_event(&events[4], Some(accounts.alice), Some(accounts.eve), 10);
+        }
+
+        #[ink::test]
+        fn transfer_fee_burns_and_skips_exempt_accounts() {
+            let accounts = accounts();
+            let mut token = setup(10_000);
+            assert_eq!(token.set_transfer_fee(100, None), Ok(()));
+            assert_eq!(token.approve(accounts.bob, 1_000), Ok(()));
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.set_fee_exempt(accounts.bob, true),
+                Err(OwnableError::CallerIsNotOwner.into())
+            );
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.charlie, 1_000, vec![]),
+                Ok(())
+            );
+            assert_eq!(token.balance_of(accounts.charlie), 990);
+            assert_eq!(token.total_supply(), 9_990);
+
+            set_sender(accounts.alice);
+            assert_eq!(token.set_fee_exempt(accounts.django, true), Ok(()));
+            assert_eq!(token.transfer(accounts.django, 1_000, vec![]), Ok(()));
+            assert_eq!(token.balance_of(accounts.django), 1_000);
+            assert_eq!(token.total_supply(), 9_990);
+        }
+
+        #[ink::test]
+        fn vesting_releases_linearly_after_cliff() {
+            let accounts = accounts();
+            let mut token = setup(1_000);
+            assert_eq!(
+                token.create_vesting(accounts.bob, 400, 1_000, 1_000, 4_000, false),
+                Err(PSP22Error::AccessControlError(
+                    AccessControlError::MissingRole
+                ))
+            );
+            assert_eq!(
+                token.grant_role(VESTING_ADMIN, Some(accounts.alice)),
+                Ok(())
+            );
+            assert_eq!(
+                token.create_vesting(accounts.bob, 400, 1_000, 5_000, 4_000, false),
+                Err(PSP22Error::InvalidVestingSchedule)
+            );
+            assert_eq!(
+                token.create_vesting(accounts.bob, 400, 1_000, 1_000, 4_000, false),
+                Ok(0)
+            );
+            assert_eq!(token.balance_of(accounts.alice), 600);
+            assert_eq!(token.balance_of(accounts.frank), 400);
+
+            ink::env::test::set_block_timestamp::<DefaultEnvironment>(1_999);
+            assert_eq!(token.releasable(0), Ok(0));
+            ink::env::test::set_block_timestamp::<DefaultEnvironment>(3_000);
+            assert_eq!(token.releasable(0), Ok(200));
+            assert_eq!(token.release(0), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 200);
+            assert_eq!(token.releasable(0), Ok(0));
+            assert_eq!(token.revoke(0), Err(PSP22Error::VestingNotRevocable));
+
+            ink::env::test::set_block_timestamp::<DefaultEnvironment>(6_000);
+            assert_eq!(token.release(0), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 400);
+            assert_eq!(token.balance_of(accounts.frank), 0);
+            assert_eq!(
+                token.releasable(1),
+                Err(PSP22Error::VestingScheduleNotFound)
+            );
+
+            let events = decode_events();
+            assert!(matches!(
+                events[5],
+                Event::VestingCreated(VestingCreated { schedule_id: 0, beneficiary, amount: 400 })
+                    if beneficiary == accounts.bob
+            ));
+            assert!(matches!(
+                events.last(),
+                Some(Event::VestingReleased(VestingReleased {
+                    schedule_id: 0,
+                    amount: 200,
+                    ..
+                }))
+            ));
+        }
+
+        #[ink::test]
+        fn vesting_revoke_returns_unvested() {
+            let accounts = accounts();
+            let mut token = setup(1_000);
+            assert_eq!(
+                token.grant_role(VESTING_ADMIN, Some(accounts.alice)),
+                Ok(())
+            );
+            assert_eq!(
+                token.create_vesting(accounts.bob, 400, 0, 0, 4_000, true),
+                Ok(0)
+            );
+            assert_eq!(
+                token.grant_role(VESTING_ADMIN, Some(accounts.charlie)),
+                Ok(())
+            );
+
+            // The unvested tokens go back to alice who funded the schedule
+            set_sender(accounts.charlie);
+            ink::env::test::set_block_timestamp::<DefaultEnvironment>(1_000);
+            assert_eq!(token.revoke(0), Ok(()));
+            assert_eq!(token.balance_of(accounts.alice), 900);
+            assert_eq!(token.balance_of(accounts.charlie), 0);
+            assert_eq!(token.revoke(0), Err(PSP22Error::VestingNotRevocable));
+
+            ink::env::test::set_block_timestamp::<DefaultEnvironment>(4_000);
+            assert_eq!(token.releasable(0), Ok(100));
+            assert_eq!(token.release(0), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert_eq!(token.balance_of(accounts.frank), 0);
+        }
+
+        #[ink::test]
+        fn vesting_handles_large_grants() {
+            let accounts = accounts();
+            let schedule = VestingSchedule {
+                funder: accounts.alice,
+                beneficiary: accounts.bob,
+                amount: u128::MAX / 2,
+                released: 0,
+                start: 0,
+                cliff: 0,
+                duration: 4_000,
+                revocable: false,
+                revoked: false,
+            };
+            assert_eq!(schedule.vested_amount(1_000), u128::MAX / 8);
+            assert_eq!(schedule.vested_amount(4_000), u128::MAX / 2);
+        }
+
+        #[ink::test]
+        fn transfer_locked_keeps_tokens_until_unlock() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.transfer_locked(accounts.bob, 60, 1_000), Ok(()));
+            assert_eq!(token.transfer(accounts.bob, 10, vec![]), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 70);
+            assert_eq!(token.locked_balance_of(accounts.bob), 60);
+            assert_eq!(token.unlocked_balance_of(accounts.bob), 10);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.transfer(accounts.charlie, 11, vec![]),
+                Err(PSP22Error::TokensLocked)
+            );
+            assert_eq!(token.burn(11), Err(PSP22Error::TokensLocked));
+            assert_eq!(token.approve(accounts.charlie, 70), Ok(()));
+            set_sender(accounts.charlie);
+            assert_eq!(
+                token.transfer_from(accounts.bob, accounts.charlie, 11, vec![]),
+                Err(PSP22Error::TokensLocked)
+            );
+
+            ink::env::test::set_block_timestamp::<DefaultEnvironment>(1_000);
+            assert_eq!(token.locked_balance_of(accounts.bob), 0);
+            assert_eq!(
+                token.transfer_from(accounts.bob, accounts.charlie, 70, vec![]),
+                Ok(())
+            );
+            assert_eq!(token.balance_of(accounts.charlie), 70);
+        }
+
+        #[ink::test]
+        fn recipient_rejects_dust_locks() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.transfer(accounts.charlie, 50, vec![]), Ok(()));
+
+            // Charlie fills every lock slot of bob with dust
+            set_sender(accounts.charlie);
+            for unlock_at in 0..MAX_LOCKS as u64 {
+                assert_eq!(
+                    token.transfer_locked(accounts.bob, 1, u64::MAX - unlock_at),
+                    Ok(())
+                );
+            }
+            set_sender(accounts.alice);
+            assert_eq!(
+                token.transfer_locked(accounts.bob, 10, 1_000),
+                Err(PSP22Error::TooManyLocks)
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(token.locks_of(accounts.bob).len(), MAX_LOCKS);
+            assert_eq!(
+                token.reject_locked(accounts.alice, u64::MAX),
+                Err(PSP22Error::LockNotFound)
+            );
+            assert_eq!(token.reject_locked(accounts.charlie, u64::MAX), Ok(()));
+            assert_eq!(
+                token.balance_of(accounts.charlie),
+                50 - MAX_LOCKS as u128 + 1
+            );
+
+            set_sender(accounts.alice);
+            assert_eq!(token.transfer_locked(accounts.bob, 10, 1_000), Ok(()));
+            assert_eq!(token.locked_balance_of(accounts.bob), MAX_LOCKS as u128 + 9);
+        }
+
+        #[ink::test]
+        fn rebase_scales_balances() {
+            let accounts = accounts();
+            ink::env::test::set_callee::<DefaultEnvironment>(accounts.frank);
+            set_sender(accounts.alice);
+            let mut token = Psp22Standard::new_elastic(CAP, None, None, 18, genesis_hash());
+            assert_eq!(token.grant_role(MINTER, Some(accounts.alice)), Ok(()));
+            assert_eq!(token.mint(accounts.alice, 300), Ok(()));
+            assert_eq!(token.transfer(accounts.bob, 100, vec![]), Ok(()));
+            assert_eq!(token.approve(accounts.charlie, 50), Ok(()));
+
+            assert_eq!(
+                token.rebase(600),
+                Err(PSP22Error::AccessControlError(
+                    AccessControlError::MissingRole
+                ))
+            );
+            assert_eq!(token.grant_role(REBASER, Some(accounts.alice)), Ok(()));
+            assert_eq!(token.rebase(CAP + 1), Err(PSP22Error::CapExceeded));
+            assert_eq!(token.rebase(0), Err(PSP22Error::InvalidRebase));
+            assert_eq!(token.rebase(600), Ok(()));
+            assert_eq!(token.total_supply(), 600);
+            assert_eq!(token.balance_of(accounts.alice), 400);
+            assert_eq!(token.balance_of(accounts.bob), 200);
+            assert_eq!(token.shares_of(accounts.bob), 100);
+            assert_eq!(token.total_shares(), 300);
+            assert_eq!(token.allowance(accounts.alice, accounts.charlie), 50);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.charlie, 50, vec![]),
+                Ok(())
+            );
+            assert_eq!(token.balance_of(accounts.charlie), 50);
+            assert_eq!(token.shares_of(accounts.charlie), 25);
+            set_sender(accounts.bob);
+            assert_eq!(token.burn(200), Ok(()));
+            assert_eq!(token.total_supply(), 400);
+            assert_eq!(token.total_shares(), 200);
+
+            let events = decode_events();
+            assert!(events.iter().any(|event| matches!(
+                event,
+                Event::Rebase(Rebase {
+                    previous_total_supply: 300,
+                    new_total_supply: 600
+                })
+            )));
+        }
+
+        #[ink::test]
+        fn elastic_supply_rejects_fixed_amount_extensions() {
+            let accounts = accounts();
+            ink::env::test::set_callee::<DefaultEnvironment>(accounts.frank);
+            set_sender(accounts.alice);
+            let mut token = Psp22Standard::new_elastic(CAP, None, None, 18, genesis_hash());
+            for role in [MINTER, SNAPSHOT, VESTING_ADMIN] {
+                assert_eq!(token.grant_role(role, Some(accounts.alice)), Ok(()));
+            }
+            assert_eq!(token.mint(accounts.alice, 300), Ok(()));
+
+            assert_eq!(token.snapshot(), Err(PSP22Error::ElasticUnsupported));
+            assert_eq!(
+                token.delegate(accounts.alice),
+                Err(PSP22Error::ElasticUnsupported)
+            );
+            assert_eq!(
+                token.get_past_votes(accounts.alice, 0),
+                Err(PSP22Error::ElasticUnsupported)
+            );
+            assert_eq!(
+                token.get_past_total_supply(0),
+                Err(PSP22Error::ElasticUnsupported)
+            );
+            assert_eq!(
+                token.transfer_locked(accounts.bob, 10, 1_000),
+                Err(PSP22Error::ElasticUnsupported)
+            );
+            assert_eq!(
+                token.create_vesting(accounts.bob, 10, 0, 0, 100, false),
+                Err(PSP22Error::ElasticUnsupported)
+            );
+            assert_eq!(token.max_flashloan(accounts.frank), 0);
+            assert_eq!(
+                token.flashloan(accounts.bob, accounts.frank, 10, Vec::new()),
+                Err(FlashLenderError::PSP22Error(PSP22Error::ElasticUnsupported))
+            );
+            assert_eq!(token.balance_of(accounts.alice), 300);
+            assert_eq!(token.get_votes(accounts.alice), 0);
+        }
+
+        #[ink::test]
+        fn rebase_requires_elastic_supply() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.grant_role(REBASER, Some(accounts.alice)), Ok(()));
+            assert_eq!(token.rebase(200), Err(PSP22Error::NotElastic));
+            assert_eq!(token.total_shares(), 100);
+        }
+
+        #[ink::test]
+        fn set_cap_works() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(
+                token.set_cap(Some(200)),
+                Err(PSP22Error::AccessControlError(
+                    AccessControlError::MissingRole
+                ))
+            );
+            assert_eq!(token.grant_role(CAP_MANAGER, Some(accounts.alice)), Ok(()));
+            assert_eq!(token.set_cap(Some(99)), Err(PSP22Error::InvalidCap));
+            assert_eq!(token.set_cap(Some(100)), Ok(()));
+            assert_eq!(token.cap(), 100);
+            assert_eq!(token.mint(accounts.bob, 1), Err(PSP22Error::CapExceeded));
+
+            assert_eq!(token.set_cap(None), Ok(()));
+            assert_eq!(token.cap(), u128::MAX);
+            assert_eq!(token.mint(accounts.bob, CAP), Ok(()));
+
+            let events = decode_events();
+            assert!(matches!(
+                events[4],
+                Event::CapChanged(CapChanged {
+                    old: Some(CAP),
+                    new: Some(100),
+                })
+            ));
+            assert!(matches!(
+                events[5],
+                Event::CapChanged(CapChanged {
+                    old: Some(100),
+                    new: None,
+                })
+            ));
+        }
+
+        #[ink::test]
+        fn burn_works_for_holder() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.transfer(accounts.bob, 40, Vec::new()), Ok(()));
+            set_sender(accounts.bob);
+            assert_eq!(token.burn(15), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 25);
+            assert_eq!(token.total_supply(), 85);
+            assert_eq!(token.burn(26), Err(PSP22Error::InsufficientBalance));
+
+            let events = decode_events();
+            assert_eq!(events.len(), 5);
+            assert_transfer_event(&events[4], Some(accounts.bob), None, 15);
+        }
+
+        #[ink::test]
+        fn burn_from_spends_allowance() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.approve(accounts.bob, 30), Ok(()));
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.burn_from(accounts.alice, 31),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+            assert_eq!(token.burn_from(accounts.alice, 20), Ok(()));
+            assert_eq!(token.balance_of(accounts.alice), 80);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 10);
+            assert_eq!(token.total_supply(), 80);
+
+            let events = decode_events();
+            assert_eq!(events.len(), 6);
+            assert_transfer_event(&events[4], Some(accounts.alice), None, 20);
+            assert_approval_event(&events[5], accounts.alice, accounts.bob, 10);
+        }
+
+        #[ink::test]
+        fn force_burn_requires_role() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(
+                token.force_burn(accounts.alice, 10),
+                Err(PSP22Error::AccessControlError(
+                    AccessControlError::MissingRole
+                ))
+            );
+            assert_eq!(token.grant_role(FORCE_BURNER, Some(accounts.bob)), Ok(()));
+
+            set_sender(accounts.bob);
+            assert_eq!(token.force_burn(accounts.alice, 10), Ok(()));
+            assert_eq!(token.balance_of(accounts.alice), 90);
+            assert_eq!(token.total_supply(), 90);
+        }
+
+        #[ink::test]
+        fn force_burn_releases_only_the_locks_it_needs() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.grant_role(FORCE_BURNER, Some(accounts.alice)), Ok(()));
+            assert_eq!(token.transfer_locked(accounts.bob, 30, 1_000), Ok(()));
+            assert_eq!(token.transfer_locked(accounts.bob, 30, 2_000), Ok(()));
+            assert_eq!(token.transfer(accounts.bob, 20, vec![]), Ok(()));
+
+            // The 20 free tokens go first, then 10 of the latest lock
+            assert_eq!(token.force_burn(accounts.bob, 30), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 50);
+            assert_eq!(token.locked_balance_of(accounts.bob), 50);
+            assert_eq!(token.unlocked_balance_of(accounts.bob), 0);
+
+            ink::env::test::set_block_timestamp::<DefaultEnvironment>(1_000);
+            assert_eq!(token.locked_balance_of(accounts.bob), 20);
+        }
+
+        #[ink::test]
+        fn transfer_ownership_works() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            assert_eq!(
+                token.transfer_ownership(None),
+                Err(OwnableError::NewOwnerIsNotSet)
+            );
+            assert_eq!(token.transfer_ownership(Some(accounts.bob)), Ok(()));
+            assert_eq!(token.owner(), Some(accounts.alice));
+            assert_eq!(token.pending_owner(), Some(accounts.bob));
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                token.accept_ownership(),
+                Err(OwnableError::CallerIsNotPendingOwner)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(token.accept_ownership(), Ok(()));
+            assert_eq!(token.owner(), Some(accounts.bob));
+            assert_eq!(token.pending_owner(), None);
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                token.transfer_ownership(Some(accounts.alice)),
+                Err(OwnableError::CallerIsNotOwner)
+            );
+
+            let events = decode_events();
+            assert_eq!(events.len(), 4);
+            assert!(matches!(
+                events[2],
+                Event::OwnershipTransferStarted(OwnershipTransferStarted { old_owner, new_owner })
+                    if old_owner == Some(accounts.alice) && new_owner == Some(accounts.bob)
+            ));
+            if let Event::OwnershipTransferred(OwnershipTransferred {
+                old_owner,
+                new_owner,
+            }) = &events[3]
+            {
+                assert_eq!(*old_owner, Some(accounts.alice));
+                assert_eq!(*new_owner, Some(accounts.bob));
+            } else {
+                panic!("encountered unexpected event kind: expected an OwnershipTransferred event")
+            }
+        }
+
+        #[ink::test]
+        fn cancel_ownership_transfer_works() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            assert_eq!(
+                token.cancel_ownership_transfer(),
+                Err(OwnableError::NewOwnerIsNotSet)
+            );
+            assert_eq!(token.transfer_ownership(Some(accounts.bob)), Ok(()));
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.cancel_ownership_transfer(),
+                Err(OwnableError::CallerIsNotOwner)
+            );
+            set_sender(accounts.alice);
+            assert_eq!(token.cancel_ownership_transfer(), Ok(()));
+            assert_eq!(token.pending_owner(), None);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.accept_ownership(),
+                Err(OwnableError::CallerIsNotPendingOwner)
+            );
+            assert_eq!(token.owner(), Some(accounts.alice));
+        }
+
+        #[ink::test]
+        fn upgrade_timelock_works() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            let code_hash = Hash::from([1; 32]);
+            assert_eq!(token.upgrade_delay(), MIN_UPGRADE_DELAY);
+            assert_eq!(
+                token.set_upgrade_delay(MIN_UPGRADE_DELAY - 1),
+                Err(UpgradeableError::InvalidDelay)
+            );
+            assert_eq!(
+                token.execute_upgrade(),
+                Err(UpgradeableError::NoPendingUpgrade)
+            );
+            assert_eq!(
+                token.schedule_upgrade(code_hash, MIN_UPGRADE_DELAY - 1),
+                Err(UpgradeableError::EtaTooEarly)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.schedule_upgrade(code_hash, MIN_UPGRADE_DELAY),
+                Err(UpgradeableError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+
+            set_sender(accounts.alice);
+            assert_eq!(token.schedule_upgrade(code_hash, MIN_UPGRADE_DELAY), Ok(()));
+            assert_eq!(
+                token.pending_upgrade(),
+                Some((code_hash, MIN_UPGRADE_DELAY))
+            );
+            assert_eq!(
+                token.execute_upgrade(),
+                Err(UpgradeableError::UpgradeNotReady)
+            );
+            assert_eq!(token.cancel_upgrade(), Ok(()));
+            assert_eq!(token.pending_upgrade(), None);
+            assert_eq!(
+                token.cancel_upgrade(),
+                Err(UpgradeableError::NoPendingUpgrade)
+            );
+
+            let events = decode_events();
+            assert_eq!(events.len(), 4);
+            assert!(matches!(
+                events[2],
+                Event::UpgradeScheduled(UpgradeScheduled { code_hash: hash, eta })
+                    if hash == code_hash && eta == MIN_UPGRADE_DELAY
+            ));
+            assert!(matches!(
+                events[3],
+                Event::UpgradeCancelled(UpgradeCancelled { code_hash: hash }) if hash == code_hash
+            ));
+        }
+
+        #[ink::test]
+        fn migrate_works() {
+            let mut token = setup(100);
+            assert_eq!(token.storage_version(), STORAGE_VERSION);
+            assert_eq!(token.migrate(), Err(UpgradeableError::AlreadyMigrated));
+
+            token.migration._set_version(0);
+            assert_eq!(token.migrate(), Ok(()));
+            assert_eq!(token.storage_version(), STORAGE_VERSION);
+            assert_eq!(token.balance_of(accounts().alice), 100);
+
+            let events = decode_events();
+            assert!(matches!(
+                events.last(),
+                Some(Event::Migrated(Migrated { from_version: 0, to_version }))
+                    if *to_version == STORAGE_VERSION
+            ));
+        }
+
+        #[ink::test]
+        fn migrate_moves_packed_root_into_cells() {
+            let accounts = accounts();
+            ink::env::test::set_callee::<DefaultEnvironment>(accounts.frank);
+            // A version 1 deployment, its fields packed into the root cell
+            let root_key = <Psp22Standard as ink::storage::traits::StorageKey>::KEY;
+            ink::env::set_contract_storage(
+                &root_key,
+                &RootV1 {
+                    total_supply: 100,
+                    current_snapshot_id: 2,
+                    name: Some(String::from("Token")),
+                    symbol: Some(String::from("TKN")),
+                    decimals: 18,
+                    owner: Some(accounts.alice),
+                    pending_owner: Some(accounts.bob),
+                    cap: CAP,
+                    paused: true,
+                    collateral: 0,
+                    underlying: Some(accounts.eve),
+                    deposited: 100,
+                    flash_fee: 30,
+                    flash_fee_recipient: Some(accounts.django),
+                    upgrade_delay: MIN_UPGRADE_DELAY,
+                    pending_upgrade: None,
+                },
+            );
+            let mut token = Psp22Standard::default();
+            token.migration._set_version(1);
+
+            assert_eq!(token.migrate(), Ok(()));
+            assert_eq!(token.storage_version(), STORAGE_VERSION);
+            assert_eq!(token.total_supply(), 100);
+            assert_eq!(token.data.current_snapshot_id(), 2);
+            assert_eq!(token.token_name(), Some(String::from("Token")));
+            assert_eq!(token.token_symbol(), Some(String::from("TKN")));
+            assert_eq!(token.token_decimals(), 18);
+            assert_eq!(token.owner(), Some(accounts.alice));
+            assert_eq!(token.pending_owner(), Some(accounts.bob));
+            assert_eq!(token.cap(), CAP);
+            assert!(token.paused());
+            assert!(!token.wrapped_native.enabled());
+            assert_eq!(token.underlying(), Some(accounts.eve));
+            assert_eq!(token.wrapper.deposited(), 100);
+            assert_eq!(token.flash_lender.fee(), 30);
+            assert_eq!(token.flash_lender.fee_recipient(), Some(accounts.django));
+            assert_eq!(token.upgrade_delay(), MIN_UPGRADE_DELAY);
+
+            // Nothing is packed into the root anymore, new fields cannot shift it
+            let mut root = Vec::new();
+            ink::storage::traits::Storable::encode(&token, &mut root);
+            assert!(root.is_empty());
+        }
+
+        #[ink::test]
+        fn migrate_moves_unversioned_root_into_cells() {
+            let accounts = accounts();
+            ink::env::test::set_callee::<DefaultEnvironment>(accounts.frank);
+            // An unversioned deployment, its root packing
+            // (total_supply, name, symbol, decimals, owner, cap)
+            let root_key = <Psp22Standard as ink::storage::traits::StorageKey>::KEY;
+            ink::env::set_contract_storage(
+                &root_key,
+                &(
+                    100u128,
+                    Some(String::from("Token")),
+                    Some(String::from("TKN")),
+                    18u8,
+                    Some(accounts.alice),
+                    CAP,
+                ),
+            );
+            let mut token = Psp22Standard::default();
+            assert_eq!(token.storage_version(), 0);
+
+            assert_eq!(token.migrate(), Ok(()));
+            assert_eq!(token.storage_version(), STORAGE_VERSION);
+            assert_eq!(token.total_supply(), 100);
+            assert_eq!(token.token_name(), Some(String::from("Token")));
+            assert_eq!(token.token_symbol(), Some(String::from("TKN")));
+            assert_eq!(token.token_decimals(), 18);
+            assert_eq!(token.owner(), Some(accounts.alice));
+            assert_eq!(token.pending_owner(), None);
+            assert_eq!(token.cap(), CAP);
+            assert!(!token.paused());
+            assert_eq!(token.upgrade_delay(), MIN_UPGRADE_DELAY);
+
+            ink::env::test::advance_block::<DefaultEnvironment>();
+            let block = ink::env::block_number::<DefaultEnvironment>();
+            assert_eq!(token.get_past_total_supply(block - 1), Ok(100));
+
+            let mut root = Vec::new();
+            ink::storage::traits::Storable::encode(&token, &mut root);
+            assert!(root.is_empty());
+        }
+
+        #[ink::test]
+        fn safe_transfer_check_skips_plain_accounts_and_no_op_transfers() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.transfer(accounts.bob, 10, vec![]), Ok(()));
+
+            // Calling a contract recipient is not supported off-chain, so these would panic
+            ink::env::test::set_contract::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(token.transfer(accounts.charlie, 0, vec![]), Ok(()));
+            set_sender(accounts.charlie);
+            assert_eq!(token.transfer(accounts.charlie, 10, vec![]), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 10);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "off-chain environment does not support contract invocation")]
+        fn safe_transfer_check_calls_contract_recipients() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            ink::env::test::set_contract::<DefaultEnvironment>(accounts.charlie);
+            let _ = token.transfer(accounts.charlie, 10, vec![]);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "storage migration required")]
+        fn messages_blocked_until_migrated() {
+            let mut token = setup(100);
+            token.migration._set_version(0);
+            let _ = token.transfer(accounts().bob, 10, vec![]);
+        }
+
+        #[ink::test]
+        fn renounce_ownership_works() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.renounce_ownership(),
+                Err(OwnableError::CallerIsNotOwner)
+            );
+            set_sender(accounts.alice);
+            assert_eq!(token.renounce_ownership(), Ok(()));
+            assert_eq!(token.owner(), None);
+
+            let events = decode_events();
+            assert_eq!(events.len(), 3);
+            if let Event::OwnershipTransferred(OwnershipTransferred {
+                old_owner,
+                new_owner,
+            }) = &events[2]
+            {
+                assert_eq!(*old_owner, Some(accounts.alice));
+                assert_eq!(*new_owner, None);
+            } else {
+                panic!("encountered unexpected event kind: expected an OwnershipTransferred event")
+            }
+        }
+
+        #[ink::test]
+        fn grant_and_revoke_role_work() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            assert_eq!(
+                token.get_role_admin(MINTER),
+                AccessControlData::_default_admin()
+            );
+            assert_eq!(token.grant_role(MINTER, Some(accounts.bob)), Ok(()));
+            assert!(token.has_role(MINTER, Some(accounts.bob)));
+            assert_eq!(
+                token.grant_role(MINTER, Some(accounts.bob)),
+                Err(AccessControlError::RoleRedundant)
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.grant_role(MINTER, Some(accounts.charlie)),
+                Err(AccessControlError::MissingRole)
+            );
+            assert_eq!(
+                token.revoke_role(MINTER, Some(accounts.alice)),
+                Err(AccessControlError::MissingRole)
+            );
+
+            set_sender(accounts.alice);
+            assert_eq!(token.revoke_role(MINTER, Some(accounts.bob)), Ok(()));
+            assert!(!token.has_role(MINTER, Some(accounts.bob)));
+            assert_eq!(
+                token.revoke_role(MINTER, Some(accounts.bob)),
+                Err(AccessControlError::MissingRole)
+            );
+        }
+
+        #[ink::test]
+        fn renounce_role_works() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            assert_eq!(
+                token.renounce_role(MINTER, Some(accounts.bob)),
+                Err(AccessControlError::InvalidCaller)
+            );
+            assert_eq!(token.renounce_role(MINTER, Some(accounts.alice)), Ok(()));
+            assert!(!token.has_role(MINTER, Some(accounts.alice)));
+            assert_eq!(
+                token.renounce_role(MINTER, Some(accounts.alice)),
+                Err(AccessControlError::MissingRole)
+            );
+            assert_eq!(
+                token.mint(accounts.alice, 1),
+                Err(PSP22Error::AccessControlError(
+                    AccessControlError::MissingRole
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn role_changes_emit_events() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            assert_eq!(token.grant_role(PAUSER, Some(accounts.bob)), Ok(()));
+            assert_eq!(token.revoke_role(PAUSER, Some(accounts.bob)), Ok(()));
+            assert_eq!(token.renounce_role(MINTER, Some(accounts.alice)), Ok(()));
+
+            let events = decode_events();
+            assert_eq!(events.len(), 5);
+            assert!(matches!(
+                events[0],
+                Event::RoleGranted(RoleGranted { role: 0, grantee, grantor: None })
+                    if grantee == Some(accounts.alice)
+            ));
+            assert!(matches!(
+                events[2],
+                Event::RoleGranted(RoleGranted { role: PAUSER, grantee, grantor })
+                    if grantee == Some(accounts.bob) && grantor == Some(accounts.alice)
+            ));
+            assert!(matches!(
+                events[3],
+                Event::RoleRevoked(RoleRevoked { role: PAUSER, account, sender })
+                    if account == Some(accounts.bob) && sender == accounts.alice
+            ));
+            assert!(matches!(
+                events[4],
+                Event::RoleRevoked(RoleRevoked { role: MINTER, account, sender })
+                    if account == Some(accounts.alice) && sender == accounts.alice
+            ));
+        }
+
+        #[ink::test]
+        fn set_role_admin_works() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            assert_eq!(token.grant_role(PAUSER, Some(accounts.bob)), Ok(()));
+            assert_eq!(token.set_role_admin(MINTER, PAUSER), Ok(()));
+            assert_eq!(token.get_role_admin(MINTER), PAUSER);
+            assert_eq!(
+                token.grant_role(MINTER, Some(accounts.charlie)),
+                Err(AccessControlError::MissingRole)
+            );
+            assert_eq!(
+                token.set_role_admin(MINTER, 0),
+                Err(AccessControlError::MissingRole)
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(token.grant_role(MINTER, Some(accounts.charlie)), Ok(()));
+            assert_eq!(token.set_role_admin(MINTER, 0), Ok(()));
+            assert_eq!(token.get_role_admin(MINTER), 0);
+
+            let events = decode_events();
+            assert!(matches!(
+                events[3],
+                Event::RoleAdminChanged(RoleAdminChanged {
+                    role: MINTER,
+                    previous_admin: 0,
+                    new_admin: PAUSER,
+                })
+            ));
+            assert!(matches!(
+                events[5],
+                Event::RoleAdminChanged(RoleAdminChanged {
+                    role: MINTER,
+                    previous_admin: PAUSER,
+                    new_admin: 0,
+                })
+            ));
+        }
+
+        #[ink::test]
+        fn role_members_are_enumerable() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            assert_eq!(token.get_role_member_count(0), 1);
+            assert_eq!(token.get_role_member(0, 0), Some(accounts.alice));
+
+            for account in [accounts.bob, accounts.charlie, accounts.django] {
+                assert_eq!(token.grant_role(MINTER, Some(account)), Ok(()));
+            }
+            assert_eq!(token.get_role_member_count(MINTER), 4);
+
+            assert_eq!(token.revoke_role(MINTER, Some(accounts.bob)), Ok(()));
+            set_sender(accounts.django);
+            assert_eq!(token.renounce_role(MINTER, Some(accounts.django)), Ok(()));
+
+            assert_eq!(token.get_role_member_count(MINTER), 2);
+            let members: Vec<_> = (0..token.get_role_member_count(MINTER))
+                .map(|index| token.get_role_member(MINTER, index))
+                .collect();
+            assert_eq!(members, vec![Some(accounts.alice), Some(accounts.charlie)]);
+            assert_eq!(token.get_role_member(MINTER, 2), None);
+        }
+
+        #[ink::test]
+        fn admin_indexes_members_granted_before_enumeration() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            // Granted by an earlier version, which did not index members
+            for account in [accounts.bob, accounts.charlie] {
+                token.admin.members.insert((MINTER, Some(account)), &());
+            }
+            assert!(token.has_role(MINTER, Some(accounts.bob)));
+            assert_eq!(token.get_role_member_count(MINTER), 1);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.index_role_members(MINTER, vec![Some(accounts.bob)]),
+                Err(AccessControlError::MissingRole)
+            );
+            set_sender(accounts.alice);
+            assert_eq!(
+                token.index_role_members(MINTER, vec![Some(accounts.django)]),
+                Err(AccessControlError::MissingRole)
+            );
+            assert_eq!(
+                token.index_role_members(
+                    MINTER,
+                    vec![
+                        Some(accounts.bob),
+                        Some(accounts.charlie),
+                        Some(accounts.alice)
+                    ]
+                ),
+                Ok(())
+            );
+            assert_eq!(token.get_role_member_count(MINTER), 3);
+            assert_eq!(token.get_role_member(MINTER, 1), Some(accounts.bob));
+            assert_eq!(token.get_role_member(MINTER, 2), Some(accounts.charlie));
+
+            assert_eq!(token.revoke_role(MINTER, Some(accounts.bob)), Ok(()));
+            assert_eq!(token.get_role_member_count(MINTER), 2);
+            assert_eq!(token.get_role_member(MINTER, 1), Some(accounts.charlie));
+        }
+
+        #[ink::test]
+        fn withdraw_fee_works() {
+            let accounts = accounts();
+            let mut token = setup(0);
+            let contract = ink::env::test::callee::<DefaultEnvironment>();
+            ink::env::test::set_account_balance::<DefaultEnvironment>(contract, 1_000);
+            let bob_balance =
+                ink::env::test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+
+            assert_eq!(token.get_balance(), 1_000);
+            assert_eq!(
+                token.withdraw_fee(1_001, accounts.bob),
+                Err(Error::NotEnoughBalance)
+            );
+            assert_eq!(token.withdraw_fee(400, accounts.bob), Ok(()));
+            assert_eq!(token.get_balance(), 600);
+            assert_eq!(
+                ink::env::test::get_account_balance::<DefaultEnvironment>(accounts.bob),
+                Ok(bob_balance + 400)
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.withdraw_fee(100, accounts.bob),
+                Err(Error::OwnableError(OwnableError::CallerIsNotOwner))
+            );
+        }
+
+        #[ink::test]
+        fn wrapped_native_deposit_and_withdraw_work() {
+            let accounts = accounts();
+            let mut token = setup_wrapped_native(CAP);
+            let contract = ink::env::test::callee::<DefaultEnvironment>();
+            ink::env::test::set_account_balance::<DefaultEnvironment>(contract, 100);
+
+            set_sender(accounts.bob);
+            ink::env::test::transfer_in::<DefaultEnvironment>(500);
+            assert_eq!(token.deposit(), Ok(()));
+            ink::env::test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(token.balance_of(accounts.bob), 500);
+            assert_eq!(token.total_supply(), 500);
+            assert_eq!(token.collateral(), 500);
+
+            let bob_balance =
+                ink::env::test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+            assert_eq!(token.withdraw(200), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 300);
+            assert_eq!(token.total_supply(), 300);
+            assert_eq!(token.collateral(), 300);
+            assert_eq!(
+                ink::env::test::get_account_balance::<DefaultEnvironment>(accounts.bob),
+                Ok(bob_balance + 200)
+            );
+            assert_eq!(token.withdraw(301), Err(PSP22Error::InsufficientCollateral));
+
+            let events = decode_events();
+            assert_transfer_event(&events[2], None, Some(accounts.bob), 500);
+            assert_transfer_event(&events[3], Some(accounts.bob), None, 200);
+        }
+
+        #[ink::test]
+        fn wrapped_native_collateral_backs_only_deposits() {
+            let accounts = accounts();
+            let mut token = setup_wrapped_native(CAP);
+            set_sender(accounts.bob);
+            ink::env::test::transfer_in::<DefaultEnvironment>(500);
+            assert_eq!(token.deposit(), Ok(()));
+            ink::env::test::set_value_transferred::<DefaultEnvironment>(0);
+
+            // Alice holds MINTER, yet cannot mint herself a claim on bob's deposit
+            set_sender(accounts.alice);
+            assert_eq!(
+                token.mint(accounts.alice, 500),
+                Err(PSP22Error::MintingDisabled)
+            );
+            assert_eq!(
+                token.batch_mint(vec![(accounts.alice, 500)]),
+                Err(PSP22Error::MintingDisabled)
+            );
+            assert_eq!(token.withdraw(500), Err(PSP22Error::InsufficientBalance));
+            assert_eq!(token.total_supply(), 500);
+        }
+
+        #[ink::test]
+        fn wrapped_native_requires_native_mode() {
+            let mut token = setup(100);
+            ink::env::test::set_value_transferred::<DefaultEnvironment>(10);
+            assert_eq!(token.deposit(), Err(PSP22Error::NotWrappedNative));
+            ink::env::test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(token.withdraw(10), Err(PSP22Error::NotWrappedNative));
+            assert_eq!(token.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn blocked_holders_cannot_claim_dividends() {
+            let accounts = accounts();
+            let mut token = setup(300);
+            let contract = ink::env::test::callee::<DefaultEnvironment>();
+            ink::env::test::set_account_balance::<DefaultEnvironment>(contract, 0);
+            assert_eq!(token.transfer(accounts.bob, 100, vec![]), Ok(()));
+            assert_eq!(token.grant_role(COMPLIANCE, Some(accounts.alice)), Ok(()));
+            assert_eq!(token.block_account(accounts.bob), Ok(()));
+
+            set_sender(accounts.charlie);
+            ink::env::test::transfer_in::<DefaultEnvironment>(300);
+            assert_eq!(token.distribute(), Ok(()));
+            ink::env::test::set_value_transferred::<DefaultEnvironment>(0);
+
+            set_sender(accounts.bob);
+            assert_eq!(token.claim(), Err(PSP22Error::AccountRestricted));
+            assert_eq!(token.withdrawable_dividend_of(accounts.bob), 100);
+
+            set_sender(accounts.alice);
+            assert_eq!(token.unblock_account(accounts.bob), Ok(()));
+            set_sender(accounts.bob);
+            assert_eq!(token.claim(), Ok(()));
+            assert_eq!(token.withdrawable_dividend_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn dividends_skip_tokens_held_by_the_contract() {
+            let accounts = accounts();
+            let mut token = setup(300);
+            let contract = ink::env::test::callee::<DefaultEnvironment>();
+            ink::env::test::set_account_balance::<DefaultEnvironment>(contract, 0);
+            assert_eq!(
+                token.grant_role(VESTING_ADMIN, Some(accounts.alice)),
+                Ok(())
+            );
+            assert_eq!(
+                token.create_vesting(accounts.bob, 100, 0, 0, 1_000, false),
+                Ok(0)
+            );
+            assert_eq!(token.balance_of(contract), 100);
+
+            set_sender(accounts.charlie);
+            ink::env::test::transfer_in::<DefaultEnvironment>(400);
+            assert_eq!(token.distribute(), Ok(()));
+            ink::env::test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(token.withdrawable_dividend_of(accounts.alice), 400);
+            assert_eq!(token.withdrawable_dividend_of(contract), 0);
+        }
+
+        #[ink::test]
+        fn native_dividends_are_distributed_pro_rata() {
+            let accounts = accounts();
+            let mut token = setup(300);
+            let contract = ink::env::test::callee::<DefaultEnvironment>();
+            ink::env::test::set_account_balance::<DefaultEnvironment>(contract, 0);
+            assert_eq!(token.transfer(accounts.bob, 100, vec![]), Ok(()));
+
+            set_sender(accounts.charlie);
+            ink::env::test::transfer_in::<DefaultEnvironment>(600);
+            assert_eq!(token.distribute(), Ok(()));
+            ink::env::test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(token.withdrawable_dividend_of(accounts.alice), 400);
+            assert_eq!(token.withdrawable_dividend_of(accounts.bob), 200);
+
+            // Balance changes after a distribution do not move earned dividends
+            set_sender(accounts.alice);
+            assert_eq!(token.transfer(accounts.bob, 200, vec![]), Ok(()));
+            assert_eq!(token.withdrawable_dividend_of(accounts.alice), 400);
+            assert_eq!(token.withdrawable_dividend_of(accounts.bob), 200);
+            assert_eq!(
+                token.withdraw_fee(1, accounts.django),
+                Err(Error::NotEnoughBalance)
+            );
+
+            set_sender(accounts.bob);
+            let bob_balance =
+                ink::env::test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+            assert_eq!(token.claim(), Ok(()));
+            assert_eq!(
+                ink::env::test::get_account_balance::<DefaultEnvironment>(accounts.bob),
+                Ok(bob_balance + 200)
+            );
+            assert_eq!(token.withdrawable_dividend_of(accounts.bob), 0);
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                token.set_reward_token(Some(accounts.eve)),
+                Err(PSP22Error::InvalidRewardAsset)
+            );
+            assert_eq!(
+                token.distribute_token(10),
+                Err(PSP22Error::InvalidRewardAsset)
+            );
+        }
+
+        #[ink::test]
+        fn wrapped_native_respects_cap_and_collateral() {
+            let accounts = accounts();
+            let mut token = setup_wrapped_native(10);
+            let contract = ink::env::test::callee::<DefaultEnvironment>();
+            ink::env::test::set_account_balance::<DefaultEnvironment>(contract, 100);
+
+            ink::env::test::set_value_transferred::<DefaultEnvironment>(11);
+            assert_eq!(token.deposit(), Err(PSP22Error::CapExceeded));
+
+            set_sender(accounts.bob);
+            ink::env::test::transfer_in::<DefaultEnvironment>(10);
+            assert_eq!(token.deposit(), Ok(()));
+            ink::env::test::set_value_transferred::<DefaultEnvironment>(0);
+
+            // Only the balance held before the deposit can be withdrawn as fees
+            set_sender(accounts.alice);
+            assert_eq!(token.get_balance(), 110);
+            assert_eq!(
+                token.withdraw_fee(101, accounts.django),
+                Err(Error::NotEnoughBalance)
+            );
+            assert_eq!(token.withdraw_fee(100, accounts.django), Ok(()));
+            assert_eq!(token.get_balance(), token.collateral());
+        }
+
+        #[ink::test]
+        fn wrapper_requires_underlying() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(token.underlying(), None);
+            assert_eq!(
+                token.deposit_for(accounts.alice, 10),
+                Err(PSP22Error::UnderlyingNotSet)
+            );
+            assert_eq!(
+                token.withdraw_to(accounts.alice, 10),
+                Err(PSP22Error::UnderlyingNotSet)
+            );
+            assert_eq!(
+                token.recover(accounts.alice),
+                Err(PSP22Error::UnderlyingNotSet)
+            );
+            assert_eq!(token.balance_of(accounts.alice), 100);
+        }
+
+        #[ink::test]
+        fn wrapper_checks_before_calling_underlying() {
+            let accounts = accounts();
+            set_sender(accounts.alice);
+            let mut token =
+                Psp22Standard::new_wrapper(10, None, None, 0, genesis_hash(), accounts.eve);
+            assert_eq!(token.underlying(), Some(accounts.eve));
+            assert_eq!(
+                token.deposit_for(accounts.alice, 11),
+                Err(PSP22Error::CapExceeded)
+            );
+            // Only tokens deposited through the wrapper can be withdrawn
+            assert_eq!(
+                token.withdraw_to(accounts.alice, 1),
+                Err(PSP22Error::InsufficientCollateral)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.recover(accounts.bob),
+                Err(PSP22Error::OwnableError(OwnableError::CallerIsNotOwner))
+            );
+        }
+
+        #[ink::test]
+        fn wrapper_is_backed_by_underlying_only() {
+            let accounts = accounts();
+            set_sender(accounts.alice);
+            let mut token =
+                Psp22Standard::new_wrapper(CAP, None, None, 0, genesis_hash(), accounts.eve);
+            assert_eq!(token.grant_role(MINTER, Some(accounts.alice)), Ok(()));
+            assert_eq!(
+                token.mint(accounts.alice, 10),
+                Err(PSP22Error::MintingDisabled)
+            );
+            assert_eq!(
+                token.batch_mint(vec![(accounts.alice, 10)]),
+                Err(PSP22Error::MintingDisabled)
+            );
+            ink::env::test::set_value_transferred::<DefaultEnvironment>(10);
+            assert_eq!(token.deposit(), Err(PSP22Error::NotWrappedNative));
+            ink::env::test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(token.total_supply(), 0);
+        }
+
+        #[ink::test]
+        fn flash_lender_config_works() {
+            let accounts = accounts();
+            let mut token = setup(CAP - 1_000);
+            let this = ink::env::test::callee::<DefaultEnvironment>();
+            assert_eq!(token.max_flashloan(this), 1_000);
+            assert_eq!(token.max_flashloan(accounts.bob), 0);
+            assert_eq!(token.flash_fee(this, 1_000), Ok(0));
+            assert_eq!(
+                token.flash_fee(accounts.bob, 1_000),
+                Err(FlashLenderError::WrongTokenAddress)
+            );
+
+            assert_eq!(
+                token.set_flash_fee(10_001, None),
+                Err(FlashLenderError::InvalidFee)
+            );
+            assert_eq!(token.set_flash_fee(30, Some(accounts.django)), Ok(()));
+            assert_eq!(token.flash_fee(this, 1_000), Ok(3));
+            assert_eq!(
+                token.flash_fee(this, u128::MAX),
+                Ok(1_020_847_100_762_815_390_390_123_822_295_304_634)
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.set_flash_fee(0, None),
+                Err(FlashLenderError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn flashloan_checks_token_cap_and_receiver() {
+            let accounts = accounts();
+            let mut token = setup(CAP - 1_000);
+            let this = ink::env::test::callee::<DefaultEnvironment>();
+            assert_eq!(
+                token.flashloan(accounts.bob, accounts.bob, 10, Vec::new()),
+                Err(FlashLenderError::WrongTokenAddress)
+            );
+            assert_eq!(
+                token.flashloan(accounts.bob, this, 1_001, Vec::new()),
+                Err(FlashLenderError::PSP22Error(PSP22Error::CapExceeded))
+            );
+            assert!(matches!(
+                token.flashloan(accounts.bob, this, 1_000, Vec::new()),
+                Err(FlashLenderError::BorrowerRejected(_))
+            ));
+        }
+
+        #[ink::test]
+        fn pause_blocks_balance_changes() {
+            let accounts = accounts();
+            let mut token = setup(100);
+            assert_eq!(
+                token.pause(),
+                Err(PSP22Error::AccessControlError(
+                    AccessControlError::MissingRole
+                ))
+            );
+            assert_eq!(token.grant_role(PAUSER, Some(accounts.alice)), Ok(()));
+            assert_eq!(token.unpause(), Err(PSP22Error::NotPaused));
+            assert_eq!(token.pause(), Ok(()));
+            assert!(token.paused());
+            assert_eq!(token.pause(), Err(PSP22Error::Paused));
+
+            assert_eq!(
+                token.transfer(accounts.bob, 10, Vec::new()),
+                Err(PSP22Error::Paused)
+            );
+            assert_eq!(token.mint(accounts.bob, 10), Err(PSP22Error::Paused));
+            assert_eq!(token.burn(10), Err(PSP22Error::Paused));
+            assert_eq!(token.approve(accounts.bob, 10), Ok(()));
+            set_sender(accounts.bob);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.bob, 10, Vec::new()),
+                Err(PSP22Error::Paused)
+            );
+
+            set_sender(accounts.alice);
+            assert_eq!(token.unpause(), Ok(()));
+            assert_eq!(token.transfer(accounts.bob, 10, Vec::new()), Ok(()));
+
+            let events = decode_events();
+            assert!(
+                matches!(events[4], Event::Paused(Paused { account }) if account == accounts.alice)
+            );
+            assert!(
+                matches!(events[6], Event::Unpaused(Unpaused { account }) if account == accounts.alice)
+            );
         }
     }
 }
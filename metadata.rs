@@ -1,23 +1,65 @@
-use ink::prelude::string::String;
+use crate::errors::PSP22Error;
+use ink::{
+    prelude::string::String,
+    storage::{Lazy, Mapping},
+};
+
+/// Keys of the fixed metadata fields, not usable as attribute keys.
+pub const RESERVED_KEYS: [&str; 3] = ["name", "symbol", "decimals"];
 
 #[derive(Default, Debug)]
 #[ink::storage_item]
 pub struct Metadata {
-    pub name: Option<String>,
-    pub symbol: Option<String>,
-    pub decimals: u8,
+    name: Lazy<Option<String>>,
+    symbol: Lazy<Option<String>>,
+    decimals: Lazy<u8>,
+    attributes: Mapping<String, String>,
 }
 
 impl Metadata {
     pub fn token_name(&self) -> Option<String> {
-        self.name.clone()
+        self.name.get().flatten()
     }
 
     pub fn token_symbol(&self) -> Option<String> {
-        self.symbol.clone()
+        self.symbol.get().flatten()
     }
 
     pub fn token_decimals(&self) -> u8 {
-        self.decimals
+        self.decimals.get().unwrap_or_default()
+    }
+
+    pub fn get_attribute(&self, key: String) -> Option<String> {
+        self.attributes.get(key)
+    }
+
+    pub fn _set_name(&mut self, name: Option<String>) {
+        self.name.set(&name);
+    }
+
+    pub fn _set_symbol(&mut self, symbol: Option<String>) {
+        self.symbol.set(&symbol);
+    }
+
+    pub fn _set_decimals(&mut self, decimals: u8) {
+        self.decimals.set(&decimals);
+    }
+
+    /// Sets the attribute `key`, removing it when `value` is `None`.
+    pub fn _set_attribute(
+        &mut self,
+        key: &String,
+        value: &Option<String>,
+    ) -> Result<(), PSP22Error> {
+        if RESERVED_KEYS.contains(&key.as_str()) {
+            return Err(PSP22Error::Custom(String::from("Reserved metadata key")));
+        }
+        match value {
+            Some(value) => {
+                self.attributes.insert(key, value);
+            }
+            None => self.attributes.remove(key),
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,93 @@
+use crate::traits::Hash;
+use crate::upgradeable::MIN_UPGRADE_DELAY;
+use ink::{
+    prelude::string::String,
+    primitives::AccountId,
+    storage::{traits::ManualKey, Lazy},
+};
+
+/// Version of the storage layout, bumped together with a migration step whenever
+/// the layout changes. Tokens deployed before versioning read as version `0`.
+pub const STORAGE_VERSION: u32 = 8;
+
+/// Keeps the storage version under a fixed key.
+///
+/// Since version 2 every field of the token lives in its own `Lazy` or `Mapping` cell, so
+/// the root cell decoded before each message stays empty and new fields only add new keys.
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct MigrationData {
+    version: Lazy<u32, ManualKey<0x5653_4e31>>,
+}
+
+impl MigrationData {
+    pub fn version(&self) -> u32 {
+        self.version.get().unwrap_or_default()
+    }
+
+    pub fn _set_version(&mut self, version: u32) {
+        self.version.set(&version);
+    }
+
+    pub fn _is_migrated(&self) -> bool {
+        self.version() >= STORAGE_VERSION
+    }
+}
+
+/// Root cell of the unversioned layout of the first deployments, which packed the plain
+/// fields of the data, metadata, ownership and cap items in declaration order.
+#[derive(Debug, scale::Encode, scale::Decode)]
+pub struct RootV0 {
+    pub total_supply: u128,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: u8,
+    pub owner: Option<AccountId>,
+    pub cap: u128,
+}
+
+/// Root cell of version 1, which packed the plain fields of every storage item in
+/// declaration order. Mappings already had their own keys and did not move.
+#[derive(Debug, scale::Encode, scale::Decode)]
+pub struct RootV1 {
+    pub total_supply: u128,
+    pub current_snapshot_id: u32,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: u8,
+    pub owner: Option<AccountId>,
+    pub pending_owner: Option<AccountId>,
+    pub cap: u128,
+    pub paused: bool,
+    pub collateral: u128,
+    pub underlying: Option<AccountId>,
+    pub deposited: u128,
+    pub flash_fee: u16,
+    pub flash_fee_recipient: Option<AccountId>,
+    pub upgrade_delay: u64,
+    pub pending_upgrade: Option<(Hash, u64)>,
+}
+
+impl From<RootV0> for RootV1 {
+    /// Fields introduced with version 1 take the values a new deployment started with.
+    fn from(root: RootV0) -> Self {
+        Self {
+            total_supply: root.total_supply,
+            current_snapshot_id: 0,
+            name: root.name,
+            symbol: root.symbol,
+            decimals: root.decimals,
+            owner: root.owner,
+            pending_owner: None,
+            cap: root.cap,
+            paused: false,
+            collateral: 0,
+            underlying: None,
+            deposited: 0,
+            flash_fee: 0,
+            flash_fee_recipient: None,
+            upgrade_delay: MIN_UPGRADE_DELAY,
+            pending_upgrade: None,
+        }
+    }
+}
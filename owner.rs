@@ -1,36 +1,66 @@
 use crate::OwnableError;
-use ink::primitives::AccountId;
+use ink::{primitives::AccountId, storage::Lazy};
 
 #[ink::storage_item]
 #[derive(Debug, Default)]
 pub struct OwnableData {
-    owner: Option<AccountId>,
+    owner: Lazy<Option<AccountId>>,
+    pending_owner: Lazy<Option<AccountId>>,
 }
 
 impl OwnableData {
     pub fn _init_with_owner(&mut self, account: AccountId) {
-        self.owner = Some(account);
+        self.owner.set(&Some(account));
+    }
+
+    /// Restores the owners of an older storage layout.
+    pub fn _restore(&mut self, owner: Option<AccountId>, pending_owner: Option<AccountId>) {
+        self.owner.set(&owner);
+        self.pending_owner.set(&pending_owner);
     }
 
     pub fn owner(&self) -> Option<AccountId> {
-        self.owner
+        self.owner.get().flatten()
+    }
+
+    pub fn pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner.get().flatten()
     }
 
     pub fn renounce_ownership(&mut self) -> Result<(), OwnableError> {
-        self.owner = None;
+        self.owner.set(&None);
+        self.pending_owner.set(&None);
         Ok(())
     }
 
+    /// Records `new_owner` as pending, ownership moves once it is accepted.
     pub fn transfer_ownership(&mut self, new_owner: Option<AccountId>) -> Result<(), OwnableError> {
-        if new_owner == None {
+        if new_owner.is_none() {
+            return Err(OwnableError::NewOwnerIsNotSet);
+        }
+        self.pending_owner.set(&new_owner);
+        Ok(())
+    }
+
+    pub fn accept_ownership(&mut self, account: AccountId) -> Result<(), OwnableError> {
+        if self.pending_owner() != Some(account) {
+            return Err(OwnableError::CallerIsNotPendingOwner);
+        }
+        self.owner.set(&Some(account));
+        self.pending_owner.set(&None);
+        Ok(())
+    }
+
+    pub fn cancel_ownership_transfer(&mut self) -> Result<(), OwnableError> {
+        if self.pending_owner().is_none() {
             return Err(OwnableError::NewOwnerIsNotSet);
         }
-        self.owner = new_owner;
+        self.pending_owner.set(&None);
         Ok(())
     }
 
     pub fn _check_owner(&self, account: Option<AccountId>) -> Result<(), OwnableError> {
-        if self.owner != account {
+        if self.owner() != account {
             return Err(OwnableError::CallerIsNotOwner);
         }
         Ok(())
@@ -0,0 +1,35 @@
+use crate::errors::PSP22Error;
+use ink::storage::Lazy;
+
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct PausableData {
+    paused: Lazy<bool>,
+}
+
+impl PausableData {
+    pub fn paused(&self) -> bool {
+        self.paused.get().unwrap_or_default()
+    }
+
+    pub fn _pause(&mut self) -> Result<(), PSP22Error> {
+        self._check_not_paused()?;
+        self.paused.set(&true);
+        Ok(())
+    }
+
+    pub fn _unpause(&mut self) -> Result<(), PSP22Error> {
+        if !self.paused() {
+            return Err(PSP22Error::NotPaused);
+        }
+        self.paused.set(&false);
+        Ok(())
+    }
+
+    pub fn _check_not_paused(&self) -> Result<(), PSP22Error> {
+        if self.paused() {
+            return Err(PSP22Error::Paused);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use crate::errors::PSP22Error;
+use ink::{
+    primitives::{AccountId, Hash},
+    storage::{Lazy, Mapping},
+};
+
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct PermitData {
+    genesis_hash: Lazy<Hash>,
+    nonces: Mapping<AccountId, u64>,
+}
+
+impl PermitData {
+    /// Genesis hash of the chain the token was deployed on, separating its permit domain
+    /// from deployments at the same address on other chains.
+    pub fn genesis_hash(&self) -> Hash {
+        self.genesis_hash.get().unwrap_or_default()
+    }
+
+    pub fn nonces(&self, owner: AccountId) -> u64 {
+        self.nonces.get(owner).unwrap_or_default()
+    }
+
+    pub fn _init_genesis_hash(&mut self, genesis_hash: Hash) {
+        self.genesis_hash.set(&genesis_hash);
+    }
+
+    /// Sets the genesis hash of a token deployed before the permit domain included it.
+    pub fn _set_genesis_hash(&mut self, genesis_hash: Hash) -> Result<(), PSP22Error> {
+        if self.genesis_hash() != Hash::default() {
+            return Err(PSP22Error::GenesisHashAlreadySet);
+        }
+        self.genesis_hash.set(&genesis_hash);
+        Ok(())
+    }
+
+    /// Returns the current nonce of `owner` and increments it.
+    pub fn _use_nonce(&mut self, owner: AccountId) -> u64 {
+        let nonce = self.nonces(owner);
+        self.nonces.insert(owner, &(nonce + 1));
+        nonce
+    }
+}
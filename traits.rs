@@ -3,12 +3,18 @@ use ink::{
     primitives::AccountId,
 };
 
-use crate::errors::{AccessControlError, OwnableError, PSP22Error, UpgradeableError, Error};
+use crate::data::Lock;
+use crate::errors::{
+    AccessControlError, Error, FlashBorrowerError, FlashLenderError, OwnableError, PSP22Error,
+    PSP22ReceiverError, UpgradeableError,
+};
+use crate::vesting::VestingSchedule;
 
 // Type
 use ink::env::{DefaultEnvironment, Environment};
 pub type Hash = <DefaultEnvironment as Environment>::Hash;
 pub type Balance = <DefaultEnvironment as Environment>::Balance;
+pub type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
 pub type RoleType = u32;
 
 // pub type EnvAccess = ::ink::EnvAccess<'static, DefaultEnvironment>;
@@ -56,6 +62,70 @@ pub trait PSP22 {
     ) -> Result<(), PSP22Error>;
 }
 
+#[ink::trait_definition]
+pub trait PSP22Snapshot {
+    /// Records the current balances and total supply, returning the snapshot id.
+    #[ink(message)]
+    fn snapshot(&mut self) -> Result<u32, PSP22Error>;
+    #[ink(message)]
+    fn balance_of_at(&self, account: AccountId, snapshot_id: u32) -> Result<u128, PSP22Error>;
+    #[ink(message)]
+    fn total_supply_at(&self, snapshot_id: u32) -> Result<u128, PSP22Error>;
+}
+
+#[ink::trait_definition]
+pub trait PSP22Votes {
+    #[ink(message)]
+    fn delegates(&self, account: AccountId) -> Option<AccountId>;
+    #[ink(message)]
+    fn get_votes(&self, account: AccountId) -> u128;
+    /// Votes of `account` at the end of a past `block`.
+    #[ink(message)]
+    fn get_past_votes(&self, account: AccountId, block: BlockNumber) -> Result<u128, PSP22Error>;
+    /// Total supply at the end of a past `block`.
+    #[ink(message)]
+    fn get_past_total_supply(&self, block: BlockNumber) -> Result<u128, PSP22Error>;
+    #[ink(message)]
+    fn delegate(&mut self, delegatee: AccountId) -> Result<(), PSP22Error>;
+}
+
+#[ink::trait_definition]
+pub trait PSP22Permit {
+    /// Approves `spender` to spend `value` of `owner`'s tokens using an ECDSA `signature`
+    /// of `owner` over the permit message, valid until the `deadline` timestamp.
+    #[ink(message)]
+    fn permit(
+        &mut self,
+        owner: AccountId,
+        spender: AccountId,
+        value: u128,
+        deadline: u64,
+        signature: [u8; 65],
+    ) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn nonces(&self, owner: AccountId) -> u64;
+    #[ink(message)]
+    fn domain_separator(&self) -> [u8; 32];
+    /// Sets the genesis hash bound into the domain of tokens upgraded from a version
+    /// without it. Only the owner can set it, and only once.
+    #[ink(message)]
+    fn set_genesis_hash(&mut self, genesis_hash: Hash) -> Result<(), PSP22Error>;
+}
+
+#[ink::trait_definition]
+pub trait PSP22Receiver {
+    /// Called on a contract recipient before it is credited by `transfer` or `transfer_from`.
+    /// Returning an error rejects the incoming transfer.
+    #[ink(message)]
+    fn before_received(
+        &mut self,
+        operator: AccountId,
+        from: AccountId,
+        value: u128,
+        data: Vec<u8>,
+    ) -> Result<(), PSP22ReceiverError>;
+}
+
 #[ink::trait_definition]
 pub trait PSP22Metadata {
     #[ink(message)]
@@ -66,16 +136,74 @@ pub trait PSP22Metadata {
     fn token_decimals(&self) -> u8;
 }
 
+#[ink::trait_definition]
+pub trait PSP22MetadataAdmin {
+    #[ink(message)]
+    fn set_name(&mut self, name: Option<String>) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn set_symbol(&mut self, symbol: Option<String>) -> Result<(), PSP22Error>;
+    /// Sets an extra attribute such as a logo URI or a website, `None` removing it.
+    #[ink(message)]
+    fn set_attribute(&mut self, key: String, value: Option<String>) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn get_attribute(&self, key: String) -> Option<String>;
+}
+
 #[ink::trait_definition]
 pub trait PSP22Burnable {
+    /// Burns `value` tokens of the caller.
+    #[ink(message)]
+    fn burn(&mut self, value: u128) -> Result<(), PSP22Error>;
+    /// Burns `value` tokens of `from`, spending the caller's allowance.
     #[ink(message)]
-    fn burn(&mut self, from: AccountId, value: u128) -> Result<(), PSP22Error>;
+    fn burn_from(&mut self, from: AccountId, value: u128) -> Result<(), PSP22Error>;
+    /// Burns `value` tokens of `from` without allowance, restricted to a privileged role.
+    #[ink(message)]
+    fn force_burn(&mut self, from: AccountId, value: u128) -> Result<(), PSP22Error>;
 }
 
 #[ink::trait_definition]
 pub trait PSP22Mintable {
     #[ink(message)]
     fn mint(&mut self, to: AccountId, value: u128) -> Result<(), PSP22Error>;
+    /// Mints every `(to, value)` pair, checking the cap once against their sum.
+    #[ink(message)]
+    fn batch_mint(&mut self, mints: Vec<(AccountId, u128)>) -> Result<(), PSP22Error>;
+}
+
+/// Multi-recipient transfers, reverted as a whole if any leg fails.
+#[ink::trait_definition]
+pub trait PSP22Batch {
+    #[ink(message)]
+    fn batch_transfer(&mut self, transfers: Vec<(AccountId, u128)>) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn batch_transfer_from(
+        &mut self,
+        from: AccountId,
+        transfers: Vec<(AccountId, u128)>,
+    ) -> Result<(), PSP22Error>;
+}
+
+/// Sanctions screening, enforced on both sides of every balance change.
+#[ink::trait_definition]
+pub trait PSP22Compliance {
+    #[ink(message)]
+    fn is_blocked(&self, account: AccountId) -> bool;
+    #[ink(message)]
+    fn is_allowed(&self, account: AccountId) -> bool;
+    #[ink(message)]
+    fn allowlist_mode(&self) -> bool;
+    #[ink(message)]
+    fn block_account(&mut self, account: AccountId) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn unblock_account(&mut self, account: AccountId) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn allow_account(&mut self, account: AccountId) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn disallow_account(&mut self, account: AccountId) -> Result<(), PSP22Error>;
+    /// Restricts transfers to allowed accounts when `enabled`.
+    #[ink(message)]
+    fn set_allowlist_mode(&mut self, enabled: bool) -> Result<(), PSP22Error>;
 }
 
 #[ink::trait_definition]
@@ -83,21 +211,236 @@ pub trait Ownable {
     #[ink(message)]
     fn owner(&self) -> Option<AccountId>;
     #[ink(message)]
+    fn pending_owner(&self) -> Option<AccountId>;
+    #[ink(message)]
     fn renounce_ownership(&mut self) -> Result<(), OwnableError>;
+    /// Starts a transfer to `new_owner`, who becomes owner by calling `accept_ownership`.
     #[ink(message)]
     fn transfer_ownership(&mut self, new_owner: Option<AccountId>) -> Result<(), OwnableError>;
+    #[ink(message)]
+    fn accept_ownership(&mut self) -> Result<(), OwnableError>;
+    #[ink(message)]
+    fn cancel_ownership_transfer(&mut self) -> Result<(), OwnableError>;
 }
 
 #[ink::trait_definition]
 pub trait PSP22Capped {
     #[ink(message)]
     fn cap(&self) -> u128;
+    /// Sets the cap, `None` meaning unlimited. The cap can not go below the total supply.
+    #[ink(message)]
+    fn set_cap(&mut self, new_cap: Option<u128>) -> Result<(), PSP22Error>;
+}
+
+#[ink::trait_definition]
+pub trait Pausable {
+    #[ink(message)]
+    fn paused(&self) -> bool;
+    #[ink(message)]
+    fn pause(&mut self) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn unpause(&mut self) -> Result<(), PSP22Error>;
+}
+
+#[ink::trait_definition]
+pub trait PSP22WrappedNative {
+    /// Mints wrapped tokens to the caller 1:1 for the transferred native value. Only tokens
+    /// deployed as wrapped native accept deposits, and they cannot be minted otherwise.
+    #[ink(message, payable)]
+    fn deposit(&mut self) -> Result<(), PSP22Error>;
+    /// Burns `amount` wrapped tokens of the caller and sends back the native value.
+    #[ink(message)]
+    fn withdraw(&mut self, amount: Balance) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn collateral(&self) -> Balance;
+}
+
+#[ink::trait_definition]
+pub trait PSP22Wrapper {
+    /// Pulls `amount` underlying tokens from the caller and mints wrapped tokens to `account`.
+    /// Wrappers are only minted this way or by `recover`, so every token is redeemable.
+    #[ink(message)]
+    fn deposit_for(&mut self, account: AccountId, amount: u128) -> Result<(), PSP22Error>;
+    /// Burns `amount` wrapped tokens of the caller and sends the underlying tokens to `account`.
+    #[ink(message)]
+    fn withdraw_to(&mut self, account: AccountId, amount: u128) -> Result<(), PSP22Error>;
+    /// Mints wrapped tokens to `account` for underlying tokens sent directly to the contract.
+    #[ink(message)]
+    fn recover(&mut self, account: AccountId) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn underlying(&self) -> Option<AccountId>;
+}
+
+#[ink::trait_definition]
+pub trait PSP22TransferFee {
+    /// Transfer fee in basis points, bounded by `MAX_TRANSFER_FEE`.
+    #[ink(message)]
+    fn transfer_fee(&self) -> u16;
+    /// Receiver of transfer fees, fees are burned when `None`.
+    #[ink(message)]
+    fn transfer_fee_recipient(&self) -> Option<AccountId>;
+    #[ink(message)]
+    fn is_fee_exempt(&self, account: AccountId) -> bool;
+    #[ink(message)]
+    fn set_transfer_fee(
+        &mut self,
+        fee: u16,
+        fee_recipient: Option<AccountId>,
+    ) -> Result<(), PSP22Error>;
+    /// Exempts transfers from or to `account` from the fee.
+    #[ink(message)]
+    fn set_fee_exempt(&mut self, account: AccountId, exempt: bool) -> Result<(), PSP22Error>;
+}
+
+#[ink::trait_definition]
+pub trait PSP22Lockable {
+    /// Transfers `value` to `to`, who cannot move the received tokens before the
+    /// `unlock_at` timestamp.
+    #[ink(message)]
+    fn transfer_locked(
+        &mut self,
+        to: AccountId,
+        value: u128,
+        unlock_at: u64,
+    ) -> Result<(), PSP22Error>;
+    /// Sends the tokens `sender` locked on the caller until `unlock_at` back to `sender`,
+    /// freeing their lock slot.
+    #[ink(message)]
+    fn reject_locked(&mut self, sender: AccountId, unlock_at: u64) -> Result<(), PSP22Error>;
+    /// Unexpired locks on the balance of `account`.
+    #[ink(message)]
+    fn locks_of(&self, account: AccountId) -> Vec<Lock>;
+    #[ink(message)]
+    fn locked_balance_of(&self, account: AccountId) -> u128;
+    #[ink(message)]
+    fn unlocked_balance_of(&self, account: AccountId) -> u128;
+}
+
+/// Pro rata distribution of the native currency, or of a designated PSP22 token,
+/// to token holders. Tokens held by the token contract itself, such as vesting escrow,
+/// earn nothing.
+#[ink::trait_definition]
+pub trait PSP22Dividends {
+    /// Distributes the transferred native value to holders.
+    #[ink(message, payable)]
+    fn distribute(&mut self) -> Result<(), PSP22Error>;
+    /// Pulls `amount` of the reward token from the caller, who must have approved this
+    /// contract, and distributes it to holders.
+    #[ink(message)]
+    fn distribute_token(&mut self, amount: u128) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn withdrawable_dividend_of(&self, account: AccountId) -> u128;
+    #[ink(message)]
+    fn claim(&mut self) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn reward_token(&self) -> Option<AccountId>;
+    /// Sets the PSP22 token paid as dividends, only possible before the first distribution.
+    #[ink(message)]
+    fn set_reward_token(&mut self, reward_token: Option<AccountId>) -> Result<(), PSP22Error>;
+}
+
+/// Elastic supply, balances being shares of a total supply that can be rebased. Snapshots,
+/// votes, locks, vesting and flash loans rely on fixed amounts and are unavailable with an
+/// elastic supply.
+#[ink::trait_definition]
+pub trait PSP22Rebase {
+    /// Sets the total supply, scaling every balance by `new_total_supply / total_supply`.
+    #[ink(message)]
+    fn rebase(&mut self, new_total_supply: u128) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn shares_of(&self, account: AccountId) -> u128;
+    #[ink(message)]
+    fn total_shares(&self) -> u128;
+}
+
+/// Token grants held by the contract and released linearly after a cliff.
+#[ink::trait_definition]
+pub trait PSP22Vesting {
+    /// Moves `amount` from the caller into a new schedule for `beneficiary` and returns its
+    /// id. `cliff` and `duration` are in milliseconds after the `start` timestamp.
+    #[ink(message)]
+    fn create_vesting(
+        &mut self,
+        beneficiary: AccountId,
+        amount: u128,
+        start: u64,
+        cliff: u64,
+        duration: u64,
+        revocable: bool,
+    ) -> Result<u32, PSP22Error>;
+    /// Sends the vested and not yet released amount to the beneficiary.
+    #[ink(message)]
+    fn release(&mut self, schedule_id: u32) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn releasable(&self, schedule_id: u32) -> Result<u128, PSP22Error>;
+    /// Stops a revocable schedule, returning the unvested amount to its funder.
+    #[ink(message)]
+    fn revoke(&mut self, schedule_id: u32) -> Result<(), PSP22Error>;
+    #[ink(message)]
+    fn vesting_schedule(&self, schedule_id: u32) -> Option<VestingSchedule>;
+}
+
+#[ink::trait_definition]
+pub trait PSP22FlashLender {
+    /// Maximum amount of `token` available for a flash loan.
+    #[ink(message)]
+    fn max_flashloan(&self, token: AccountId) -> u128;
+    #[ink(message)]
+    fn flash_fee(&self, token: AccountId, amount: u128) -> Result<u128, FlashLenderError>;
+    /// Mints `amount` to `receiver`, calls `PSP22FlashBorrower::on_flashloan` on it and then
+    /// takes back `amount` plus the fee through the receiver's allowance to this contract.
+    #[ink(message)]
+    fn flashloan(
+        &mut self,
+        receiver: AccountId,
+        token: AccountId,
+        amount: u128,
+        data: Vec<u8>,
+    ) -> Result<(), FlashLenderError>;
+    /// Sets the fee in basis points and its recipient, `None` burning the fee.
+    #[ink(message)]
+    fn set_flash_fee(
+        &mut self,
+        fee: u16,
+        fee_recipient: Option<AccountId>,
+    ) -> Result<(), FlashLenderError>;
+}
+
+#[ink::trait_definition]
+pub trait PSP22FlashBorrower {
+    #[ink(message)]
+    fn on_flashloan(
+        &mut self,
+        initiator: AccountId,
+        token: AccountId,
+        amount: u128,
+        fee: u128,
+        data: Vec<u8>,
+    ) -> Result<(), FlashBorrowerError>;
 }
 
 #[ink::trait_definition]
 pub trait UpgradeableTrait {
+    /// Schedules an upgrade to `new_code_hash`, executable from the `eta` timestamp
+    /// which must be at least `upgrade_delay` in the future.
     #[ink(message)]
-    fn set_code(&mut self, new_code_hash: Hash) -> Result<(), UpgradeableError>;
+    fn schedule_upgrade(&mut self, new_code_hash: Hash, eta: u64) -> Result<(), UpgradeableError>;
+    #[ink(message)]
+    fn execute_upgrade(&mut self) -> Result<(), UpgradeableError>;
+    #[ink(message)]
+    fn cancel_upgrade(&mut self) -> Result<(), UpgradeableError>;
+    #[ink(message)]
+    fn pending_upgrade(&self) -> Option<(Hash, u64)>;
+    #[ink(message)]
+    fn upgrade_delay(&self) -> u64;
+    #[ink(message)]
+    fn set_upgrade_delay(&mut self, delay: u64) -> Result<(), UpgradeableError>;
+    #[ink(message)]
+    fn storage_version(&self) -> u32;
+    /// Runs the pending storage migration steps, all other messages are blocked until
+    /// the storage version matches the code.
+    #[ink(message)]
+    fn migrate(&mut self) -> Result<(), UpgradeableError>;
 }
 
 #[ink::trait_definition]
@@ -124,6 +467,29 @@ pub trait AccessControl {
         role: RoleType,
         account: Option<AccountId>,
     ) -> Result<(), AccessControlError>;
+    #[ink(message)]
+    fn set_role_admin(
+        &mut self,
+        role: RoleType,
+        new_admin: RoleType,
+    ) -> Result<(), AccessControlError>;
+}
+
+#[ink::trait_definition]
+pub trait AccessControlEnumerable {
+    #[ink(message)]
+    fn get_role_member(&self, role: RoleType, index: u32) -> Option<AccountId>;
+    #[ink(message)]
+    fn get_role_member_count(&self, role: RoleType) -> u32;
+    /// Indexes `members` of `role` granted before members were enumerable, skipping the ones
+    /// already indexed. Only the admin of `role` can call it, and every listed account must
+    /// hold the role.
+    #[ink(message)]
+    fn index_role_members(
+        &mut self,
+        role: RoleType,
+        members: Vec<Option<AccountId>>,
+    ) -> Result<(), AccessControlError>;
 }
 
 #[ink::trait_definition]
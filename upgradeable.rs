@@ -0,0 +1,77 @@
+use crate::errors::UpgradeableError;
+use crate::traits::Hash;
+use ink::storage::Lazy;
+
+/// Lower bound of the upgrade delay, two days in milliseconds.
+pub const MIN_UPGRADE_DELAY: u64 = 2 * 24 * 60 * 60 * 1000;
+
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct UpgradeableData {
+    delay: Lazy<u64>,
+    pending: Lazy<Option<(Hash, u64)>>,
+}
+
+impl UpgradeableData {
+    pub fn _init_delay(&mut self, delay: u64) -> Result<(), UpgradeableError> {
+        self._set_delay(delay)
+    }
+
+    /// Restores the delay and pending upgrade of an older storage layout.
+    pub fn _restore(&mut self, delay: u64, pending: Option<(Hash, u64)>) {
+        self.delay.set(&delay);
+        self.pending.set(&pending);
+    }
+
+    /// Minimum time between scheduling an upgrade and executing it.
+    pub fn delay(&self) -> u64 {
+        self.delay.get().unwrap_or_default()
+    }
+
+    /// Scheduled code hash with the timestamp from which it can be executed.
+    pub fn pending_upgrade(&self) -> Option<(Hash, u64)> {
+        self.pending.get().flatten()
+    }
+
+    pub fn _set_delay(&mut self, delay: u64) -> Result<(), UpgradeableError> {
+        if delay < MIN_UPGRADE_DELAY {
+            return Err(UpgradeableError::InvalidDelay);
+        }
+        self.delay.set(&delay);
+        Ok(())
+    }
+
+    /// Schedules `code_hash`, replacing any pending upgrade.
+    pub fn _schedule(
+        &mut self,
+        code_hash: Hash,
+        eta: u64,
+        now: u64,
+    ) -> Result<(), UpgradeableError> {
+        if eta < now.saturating_add(self.delay()) {
+            return Err(UpgradeableError::EtaTooEarly);
+        }
+        self.pending.set(&Some((code_hash, eta)));
+        Ok(())
+    }
+
+    /// Clears and returns the pending code hash once its eta has passed.
+    pub fn _execute(&mut self, now: u64) -> Result<Hash, UpgradeableError> {
+        let (code_hash, eta) = self
+            .pending_upgrade()
+            .ok_or(UpgradeableError::NoPendingUpgrade)?;
+        if now < eta {
+            return Err(UpgradeableError::UpgradeNotReady);
+        }
+        self.pending.set(&None);
+        Ok(code_hash)
+    }
+
+    pub fn _cancel(&mut self) -> Result<Hash, UpgradeableError> {
+        let (code_hash, _) = self
+            .pending_upgrade()
+            .ok_or(UpgradeableError::NoPendingUpgrade)?;
+        self.pending.set(&None);
+        Ok(code_hash)
+    }
+}
@@ -0,0 +1,117 @@
+use crate::data::mul_div;
+use crate::errors::PSP22Error;
+use ink::{
+    primitives::AccountId,
+    storage::{Lazy, Mapping},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct VestingSchedule {
+    /// Account that provided the tokens, receiving back the unvested part when revoked.
+    pub funder: AccountId,
+    pub beneficiary: AccountId,
+    /// Total amount granted, reduced to the vested amount when revoked.
+    pub amount: u128,
+    pub released: u128,
+    /// Timestamps in milliseconds, `cliff` and `duration` being relative to `start`.
+    pub start: u64,
+    pub cliff: u64,
+    pub duration: u64,
+    pub revocable: bool,
+    pub revoked: bool,
+}
+
+impl VestingSchedule {
+    /// Amount vested at `now`, linearly from `start` once the cliff has passed.
+    pub fn vested_amount(&self, now: u64) -> u128 {
+        if self.revoked {
+            return self.amount;
+        }
+        let elapsed = now.saturating_sub(self.start);
+        if elapsed < self.cliff {
+            0
+        } else if elapsed >= self.duration {
+            self.amount
+        } else {
+            mul_div(self.amount, elapsed as u128, self.duration as u128)
+        }
+    }
+
+    pub fn releasable_amount(&self, now: u64) -> u128 {
+        self.vested_amount(now).saturating_sub(self.released)
+    }
+}
+
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct VestingData {
+    schedules: Mapping<u32, VestingSchedule>,
+    schedule_count: Lazy<u32>,
+}
+
+impl VestingData {
+    pub fn schedule(&self, schedule_id: u32) -> Option<VestingSchedule> {
+        self.schedules.get(schedule_id)
+    }
+
+    pub fn schedule_count(&self) -> u32 {
+        self.schedule_count.get().unwrap_or_default()
+    }
+
+    pub fn releasable(&self, schedule_id: u32, now: u64) -> Result<u128, PSP22Error> {
+        Ok(self._get(schedule_id)?.releasable_amount(now))
+    }
+
+    /// Stores a new schedule and returns its id.
+    pub fn _create(&mut self, schedule: VestingSchedule) -> Result<u32, PSP22Error> {
+        if schedule.amount == 0
+            || schedule.duration == 0
+            || schedule.cliff > schedule.duration
+            || schedule.released > 0
+            || schedule.revoked
+        {
+            return Err(PSP22Error::InvalidVestingSchedule);
+        }
+        let schedule_id = self.schedule_count();
+        self.schedules.insert(schedule_id, &schedule);
+        self.schedule_count.set(&(schedule_id + 1));
+        Ok(schedule_id)
+    }
+
+    /// Marks the releasable amount as released and returns it with the beneficiary.
+    pub fn _release(
+        &mut self,
+        schedule_id: u32,
+        now: u64,
+    ) -> Result<(AccountId, u128), PSP22Error> {
+        let mut schedule = self._get(schedule_id)?;
+        let amount = schedule.releasable_amount(now);
+        schedule.released = schedule.released.saturating_add(amount);
+        self.schedules.insert(schedule_id, &schedule);
+        Ok((schedule.beneficiary, amount))
+    }
+
+    /// Stops vesting at `now` and returns the unvested amount with the funder.
+    pub fn _revoke(&mut self, schedule_id: u32, now: u64) -> Result<(AccountId, u128), PSP22Error> {
+        let mut schedule = self._get(schedule_id)?;
+        if !schedule.revocable || schedule.revoked {
+            return Err(PSP22Error::VestingNotRevocable);
+        }
+        let vested = schedule.vested_amount(now);
+        let unvested = schedule.amount.saturating_sub(vested);
+        schedule.amount = vested;
+        schedule.revoked = true;
+        self.schedules.insert(schedule_id, &schedule);
+        Ok((schedule.funder, unvested))
+    }
+
+    fn _get(&self, schedule_id: u32) -> Result<VestingSchedule, PSP22Error> {
+        self.schedules
+            .get(schedule_id)
+            .ok_or(PSP22Error::VestingScheduleNotFound)
+    }
+}
@@ -0,0 +1,201 @@
+use crate::errors::PSP22Error;
+use crate::traits::BlockNumber;
+use ink::{
+    prelude::{vec, vec::Vec},
+    primitives::AccountId,
+    storage::Mapping,
+};
+
+pub enum VotesEvent {
+    DelegateChanged {
+        delegator: AccountId,
+        from_delegate: Option<AccountId>,
+        to_delegate: Option<AccountId>,
+    },
+    DelegateVotesChanged {
+        delegate: AccountId,
+        previous_votes: u128,
+        new_votes: u128,
+    },
+}
+
+/// Checkpoints of a delegate's votes, or of the total supply when the account is `None`.
+pub type CheckpointAccount = Option<AccountId>;
+/// `(block number, value)` valid from that block on.
+pub type Checkpoint = (BlockNumber, u128);
+
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct VotesData {
+    delegates: Mapping<AccountId, AccountId>,
+    checkpoints: Mapping<(CheckpointAccount, u32), Checkpoint>,
+    checkpoint_counts: Mapping<CheckpointAccount, u32>,
+}
+
+impl VotesData {
+    pub fn delegates(&self, account: AccountId) -> Option<AccountId> {
+        self.delegates.get(account)
+    }
+
+    pub fn get_votes(&self, account: AccountId) -> u128 {
+        self._latest(Some(account))
+    }
+
+    pub fn get_past_votes(
+        &self,
+        account: AccountId,
+        block: BlockNumber,
+        current_block: BlockNumber,
+    ) -> Result<u128, PSP22Error> {
+        self._value_at(Some(account), block, current_block)
+    }
+
+    pub fn get_past_total_supply(
+        &self,
+        block: BlockNumber,
+        current_block: BlockNumber,
+    ) -> Result<u128, PSP22Error> {
+        self._value_at(None, block, current_block)
+    }
+
+    /// Delegates all votes of `delegator`, who holds `balance` tokens, to `delegatee`.
+    pub fn _delegate(
+        &mut self,
+        delegator: AccountId,
+        delegatee: AccountId,
+        balance: u128,
+        block: BlockNumber,
+    ) -> Vec<VotesEvent> {
+        let from_delegate = self.delegates(delegator);
+        self.delegates.insert(delegator, &delegatee);
+        let mut events = vec![VotesEvent::DelegateChanged {
+            delegator,
+            from_delegate,
+            to_delegate: Some(delegatee),
+        }];
+        events.extend(self._move_voting_power(from_delegate, Some(delegatee), balance, block));
+        events
+    }
+
+    /// Moves the votes following a balance change, `None` standing for mint or burn.
+    pub fn _transfer_voting_units(
+        &mut self,
+        from: Option<AccountId>,
+        to: Option<AccountId>,
+        value: u128,
+        block: BlockNumber,
+    ) -> Vec<VotesEvent> {
+        if from.is_none() {
+            let total_supply = self._latest(None).saturating_add(value);
+            self._push_checkpoint(None, total_supply, block);
+        }
+        if to.is_none() {
+            let total_supply = self._latest(None).saturating_sub(value);
+            self._push_checkpoint(None, total_supply, block);
+        }
+        self._move_voting_power(
+            from.and_then(|account| self.delegates(account)),
+            to.and_then(|account| self.delegates(account)),
+            value,
+            block,
+        )
+    }
+
+    /// Starts the total supply checkpoints of a supply minted before votes were tracked.
+    pub fn _restore_total_supply(&mut self, total_supply: u128, block: BlockNumber) {
+        self._push_checkpoint(None, total_supply, block);
+    }
+
+    fn _move_voting_power(
+        &mut self,
+        from: Option<AccountId>,
+        to: Option<AccountId>,
+        value: u128,
+        block: BlockNumber,
+    ) -> Vec<VotesEvent> {
+        let mut events = vec![];
+        if from == to || value == 0 {
+            return events;
+        }
+        if let Some(delegate) = from {
+            let previous_votes = self._latest(Some(delegate));
+            let new_votes = previous_votes.saturating_sub(value);
+            self._push_checkpoint(Some(delegate), new_votes, block);
+            events.push(VotesEvent::DelegateVotesChanged {
+                delegate,
+                previous_votes,
+                new_votes,
+            });
+        }
+        if let Some(delegate) = to {
+            let previous_votes = self._latest(Some(delegate));
+            let new_votes = previous_votes.saturating_add(value);
+            self._push_checkpoint(Some(delegate), new_votes, block);
+            events.push(VotesEvent::DelegateVotesChanged {
+                delegate,
+                previous_votes,
+                new_votes,
+            });
+        }
+        events
+    }
+
+    fn _latest(&self, account: CheckpointAccount) -> u128 {
+        match self.checkpoint_counts.get(account).unwrap_or_default() {
+            0 => 0,
+            count => {
+                self.checkpoints
+                    .get((account, count - 1))
+                    .unwrap_or_default()
+                    .1
+            }
+        }
+    }
+
+    fn _push_checkpoint(&mut self, account: CheckpointAccount, value: u128, block: BlockNumber) {
+        let count = self.checkpoint_counts.get(account).unwrap_or_default();
+        if count > 0 {
+            let (last_block, _) = self
+                .checkpoints
+                .get((account, count - 1))
+                .unwrap_or_default();
+            if last_block == block {
+                self.checkpoints
+                    .insert((account, count - 1), &(block, value));
+                return;
+            }
+        }
+        self.checkpoints.insert((account, count), &(block, value));
+        self.checkpoint_counts.insert(account, &(count + 1));
+    }
+
+    /// Returns the value of the last checkpoint at or before `block`.
+    fn _value_at(
+        &self,
+        account: CheckpointAccount,
+        block: BlockNumber,
+        current_block: BlockNumber,
+    ) -> Result<u128, PSP22Error> {
+        if block >= current_block {
+            return Err(PSP22Error::FutureLookup);
+        }
+        let (mut low, mut high) = (0, self.checkpoint_counts.get(account).unwrap_or_default());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (checkpoint_block, _) = self.checkpoints.get((account, mid)).unwrap_or_default();
+            if checkpoint_block > block {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        match high {
+            0 => Ok(0),
+            _ => Ok(self
+                .checkpoints
+                .get((account, high - 1))
+                .unwrap_or_default()
+                .1),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::errors::PSP22Error;
+use ink::storage::Lazy;
+
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct WrappedNativeData {
+    enabled: Lazy<bool>,
+    collateral: Lazy<u128>,
+}
+
+impl WrappedNativeData {
+    /// Whether the token wraps the native currency, in which case deposits are its only supply.
+    pub fn enabled(&self) -> bool {
+        self.enabled.get().unwrap_or_default()
+    }
+
+    /// Native balance held by the contract to back wrapped tokens.
+    pub fn collateral(&self) -> u128 {
+        self.collateral.get().unwrap_or_default()
+    }
+
+    pub fn _init_enabled(&mut self) {
+        self.enabled.set(&true);
+    }
+
+    pub fn _check_enabled(&self) -> Result<(), PSP22Error> {
+        if !self.enabled() {
+            return Err(PSP22Error::NotWrappedNative);
+        }
+        Ok(())
+    }
+
+    pub fn _deposit(&mut self, value: u128) {
+        self.collateral
+            .set(&self.collateral().saturating_add(value));
+    }
+
+    pub fn _withdraw(&mut self, value: u128) -> Result<(), PSP22Error> {
+        let collateral = self.collateral();
+        if collateral < value {
+            return Err(PSP22Error::InsufficientCollateral);
+        }
+        self.collateral.set(&(collateral - value));
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use crate::errors::PSP22Error;
+use ink::{primitives::AccountId, storage::Lazy};
+
+#[ink::storage_item]
+#[derive(Debug, Default)]
+pub struct WrapperData {
+    underlying: Lazy<Option<AccountId>>,
+    deposited: Lazy<u128>,
+}
+
+impl WrapperData {
+    pub fn underlying(&self) -> Option<AccountId> {
+        self.underlying.get().flatten()
+    }
+
+    /// Amount of underlying tokens held by the contract to back wrapped tokens.
+    pub fn deposited(&self) -> u128 {
+        self.deposited.get().unwrap_or_default()
+    }
+
+    pub fn _init_underlying(&mut self, underlying: AccountId) {
+        self.underlying.set(&Some(underlying));
+    }
+
+    pub fn _check_underlying(&self) -> Result<AccountId, PSP22Error> {
+        self.underlying().ok_or(PSP22Error::UnderlyingNotSet)
+    }
+
+    pub fn _deposit(&mut self, amount: u128) {
+        self.deposited.set(&self.deposited().saturating_add(amount));
+    }
+
+    pub fn _withdraw(&mut self, amount: u128) -> Result<(), PSP22Error> {
+        let deposited = self.deposited();
+        if deposited < amount {
+            return Err(PSP22Error::InsufficientCollateral);
+        }
+        self.deposited.set(&(deposited - amount));
+        Ok(())
+    }
+}